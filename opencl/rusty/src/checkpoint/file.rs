@@ -0,0 +1,428 @@
+extern crate rulinalg;
+
+use rulinalg::vector::Vector;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// The first bytes of every checkpoint file.
+const MAGIC: &[u8; 8] = b"DGCHKPNT";
+
+// The version of the file layout written by `Checkpoint::write`. Bump it whenever the layout
+// changes, so that old files are rejected rather than misread.
+pub const VERSION: u32 = 2;
+
+// The state of a run after some number of time steps. Each element's solution is stored as
+// its components, e.g. E and H, with one value per node. The residuals are the per-element
+// registers kept between steps by low-storage Runge-Kutta methods, in the same layout, and
+// are empty for methods which keep none.
+//
+// The file layout, with every number little-endian, is
+//
+//     magic, version: u32, order: i32, step: i32, time: f64, dt: f64,
+//     n_elements: u32, n_components: u32, n_nodes: u32, has_residuals: u8,
+//     solution: [f64; n_elements * n_components * n_nodes],
+//     residuals: [f64; n_elements * n_components * n_nodes] if has_residuals.
+//
+// Floats are stored by their bits, so a run resumed from a checkpoint is bit-for-bit the
+// same as one which was never interrupted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    // The polynomial order of the reference element.
+    pub order: i32,
+    // The number of time steps taken.
+    pub step: i32,
+    pub time: f64,
+    // The length of every step, which a resumed run must keep to.
+    pub dt: f64,
+    pub solution: Vec<Vec<Vector<f64>>>,
+    pub residuals: Vec<Vec<Vector<f64>>>,
+}
+
+// Where, and every how many steps, a run writes its checkpoints. Each checkpoint replaces
+// the last.
+#[derive(Clone, Copy, Debug)]
+pub struct CheckpointSchedule<'a> {
+    path: &'a Path,
+    interval: i32,
+}
+
+impl<'a> CheckpointSchedule<'a> {
+    pub fn new(path: &'a Path, interval: i32) -> CheckpointSchedule<'a> {
+        assert!(
+            interval > 0,
+            "checkpoints must be at least one step apart, not {}",
+            interval
+        );
+        CheckpointSchedule { path, interval }
+    }
+
+    pub fn path(&self) -> &'a Path {
+        self.path
+    }
+
+    // Whether to write a checkpoint once `steps` steps have been taken.
+    pub fn is_due(&self, steps: i32) -> bool {
+        steps % self.interval == 0
+    }
+}
+
+impl Checkpoint {
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let (n_components, n_nodes) = shape(&self.solution)?;
+        let has_residuals = !self.residuals.is_empty();
+        if has_residuals && shape(&self.residuals)? != (n_components, n_nodes) {
+            return Err(invalid_data("the residuals and the solution have different shapes"));
+        }
+
+        w.write_all(MAGIC)?;
+        write_u32(w, VERSION)?;
+        write_u32(w, self.order as u32)?;
+        write_u32(w, self.step as u32)?;
+        write_u64(w, self.time.to_bits())?;
+        write_u64(w, self.dt.to_bits())?;
+        write_u32(w, self.solution.len() as u32)?;
+        write_u32(w, n_components as u32)?;
+        write_u32(w, n_nodes as u32)?;
+        w.write_all(&[has_residuals as u8])?;
+        write_fields(w, &self.solution)?;
+        if has_residuals {
+            write_fields(w, &self.residuals)?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> io::Result<Checkpoint> {
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a checkpoint file"));
+        }
+        let version = read_u32(r)?;
+        if version != VERSION {
+            return Err(invalid_data(&format!(
+                "checkpoint version {} is not supported, expected {}",
+                version, VERSION
+            )));
+        }
+
+        let order = read_u32(r)? as i32;
+        let step = read_u32(r)? as i32;
+        let time = f64::from_bits(read_u64(r)?);
+        let dt = f64::from_bits(read_u64(r)?);
+        let n_elements = read_u32(r)? as usize;
+        let n_components = read_u32(r)? as usize;
+        let n_nodes = read_u32(r)? as usize;
+        let mut has_residuals = [0u8; 1];
+        r.read_exact(&mut has_residuals)?;
+        // Every element holds at least one number, so the counts cannot run far ahead of the
+        // data that is actually there.
+        if n_elements > 0 && (n_components == 0 || n_nodes == 0) {
+            return Err(invalid_data("the checkpoint has empty elements"));
+        }
+
+        let solution = read_fields(r, n_elements, n_components, n_nodes)?;
+        let residuals = match has_residuals[0] {
+            0 => vec![],
+            1 => read_fields(r, n_elements, n_components, n_nodes)?,
+            _ => return Err(invalid_data("corrupt residuals flag")),
+        };
+
+        Ok(Checkpoint {
+            order,
+            step,
+            time,
+            dt,
+            solution,
+            residuals,
+        })
+    }
+
+    // Check that the checkpoint can be restored into a run of order `order` whose solution is
+    // laid out like `solution`: the same number of elements, with the same number of
+    // components on each and of nodes in each component. The residuals, if there are any,
+    // must be laid out the same way.
+    pub fn check_layout(&self, order: i32, solution: &Vec<Vec<Vector<f64>>>) -> io::Result<()> {
+        if self.order != order {
+            return Err(invalid_data(&format!(
+                "the checkpoint is of order {}, not {}",
+                self.order, order
+            )));
+        }
+        check_fields("solution", &self.solution, solution)?;
+        if !self.residuals.is_empty() {
+            check_fields("residuals", &self.residuals, solution)?;
+        }
+        Ok(())
+    }
+
+    // Check that the checkpoint was taken by a run with steps of exactly `dt`. Anything else,
+    // e.g. a run to another final time, would carry on from it with different steps.
+    pub fn check_time_step(&self, dt: f64) -> io::Result<()> {
+        if self.dt.to_bits() != dt.to_bits() {
+            return Err(invalid_data(&format!(
+                "the checkpoint was taken with time step {}, not {}",
+                self.dt, dt
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Checkpoint> {
+        let mut file = BufReader::new(File::open(path)?);
+        Checkpoint::read(&mut file)
+    }
+}
+
+// The number of components and nodes per element, which must be the same on every element.
+fn shape(fields: &Vec<Vec<Vector<f64>>>) -> io::Result<(usize, usize)> {
+    let n_components = fields.first().map_or(0, |components| components.len());
+    let n_nodes = fields
+        .first()
+        .and_then(|components| components.first())
+        .map_or(0, |component| component.size());
+    let ragged = fields.iter().any(|components| {
+        components.len() != n_components
+            || components.iter().any(|component| component.size() != n_nodes)
+    });
+    if ragged {
+        return Err(invalid_data("ragged checkpoint"));
+    }
+    Ok((n_components, n_nodes))
+}
+
+// Check that `fields` from a checkpoint have the same number of elements, components and
+// nodes as `expected`.
+fn check_fields(
+    name: &str,
+    fields: &Vec<Vec<Vector<f64>>>,
+    expected: &Vec<Vec<Vector<f64>>>,
+) -> io::Result<()> {
+    if fields.len() != expected.len() {
+        return Err(invalid_data(&format!(
+            "the checkpoint {} has {} elements, not {}",
+            name,
+            fields.len(),
+            expected.len()
+        )));
+    }
+    for (k, (components, expected)) in fields.iter().zip(expected.iter()).enumerate() {
+        if components.len() != expected.len() {
+            return Err(invalid_data(&format!(
+                "element {} of the checkpoint {} has {} components, not {}",
+                k,
+                name,
+                components.len(),
+                expected.len()
+            )));
+        }
+        let sizes_match = components
+            .iter()
+            .zip(expected.iter())
+            .all(|(component, expected)| component.size() == expected.size());
+        if !sizes_match {
+            return Err(invalid_data(&format!(
+                "element {} of the checkpoint {} has the wrong number of nodes",
+                k, name
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn write_fields<W: Write>(w: &mut W, fields: &Vec<Vec<Vector<f64>>>) -> io::Result<()> {
+    for components in fields.iter() {
+        for component in components.iter() {
+            for x in component.iter() {
+                write_u64(w, x.to_bits())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_fields<R: Read>(
+    r: &mut R,
+    n_elements: usize,
+    n_components: usize,
+    n_nodes: usize,
+) -> io::Result<Vec<Vec<Vector<f64>>>> {
+    // The counts come from the file, so nothing is allocated ahead of the data read.
+    let mut fields = vec![];
+    for _ in 0..n_elements {
+        let mut components = vec![];
+        for _ in 0..n_components {
+            let mut component = vec![];
+            for _ in 0..n_nodes {
+                let x = read_u64(r).map_err(|error| match error.kind() {
+                    io::ErrorKind::UnexpectedEof => {
+                        invalid_data("the checkpoint is shorter than its header says")
+                    }
+                    _ => error,
+                })?;
+                component.push(f64::from_bits(x));
+            }
+            components.push(Vector::new(component));
+        }
+        fields.push(components);
+    }
+    Ok(fields)
+}
+
+fn write_u32<W: Write>(w: &mut W, x: u32) -> io::Result<()> {
+    let bytes = [x as u8, (x >> 8) as u8, (x >> 16) as u8, (x >> 24) as u8];
+    w.write_all(&bytes)
+}
+
+fn write_u64<W: Write>(w: &mut W, x: u64) -> io::Result<()> {
+    write_u32(w, x as u32)?;
+    write_u32(w, (x >> 32) as u32)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes
+        .iter()
+        .rev()
+        .fold(0u32, |acc, &byte| (acc << 8) | byte as u32))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let low = read_u32(r)? as u64;
+    let high = read_u32(r)? as u64;
+    Ok((high << 32) | low)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Checkpoint, CheckpointSchedule, VERSION};
+    use std::f64;
+    use std::io::{Cursor, ErrorKind};
+    use std::path::Path;
+
+    fn checkpoint() -> Checkpoint {
+        let element = |k: f64| {
+            vec![
+                vector![k, -0.0, 1. / 3.],
+                vector![f64::MIN_POSITIVE, k, 1e300],
+            ]
+        };
+        Checkpoint {
+            order: 2,
+            step: 17,
+            time: 0.1 + 0.2,
+            dt: 0.3 / 17.,
+            solution: vec![element(1.), element(2.)],
+            residuals: vec![element(3.), element(4.)],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let checkpoint = checkpoint();
+        let mut bytes = vec![];
+        checkpoint.write(&mut bytes).expect("could not write the checkpoint");
+        let read = Checkpoint::read(&mut Cursor::new(bytes)).expect("could not read it back");
+        assert_eq!(read, checkpoint);
+        assert_eq!(read.time.to_bits(), checkpoint.time.to_bits());
+        assert!(read.solution[0][0][1].is_sign_negative());
+    }
+
+    #[test]
+    fn test_rejects_other_versions() {
+        let mut bytes = vec![];
+        checkpoint().write(&mut bytes).expect("could not write the checkpoint");
+        bytes[8] = (VERSION + 1) as u8;
+        assert!(Checkpoint::read(&mut Cursor::new(bytes.clone())).is_err());
+
+        bytes[0] = b'X';
+        assert!(Checkpoint::read(&mut Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_truncated_files() {
+        let mut bytes = vec![];
+        checkpoint().write(&mut bytes).expect("could not write the checkpoint");
+        bytes.pop();
+        assert!(Checkpoint::read(&mut Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_ragged_checkpoints() {
+        let mut checkpoint = checkpoint();
+        checkpoint.solution[1][0] = vector![1., 2.];
+        assert_eq!(
+            checkpoint.write(&mut vec![]).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_check_layout() {
+        let checkpoint = checkpoint();
+        let solution = checkpoint.solution.clone();
+        assert!(checkpoint.check_layout(2, &solution).is_ok());
+        assert!(checkpoint.check_layout(3, &solution).is_err());
+        assert!(checkpoint.check_layout(2, &solution[..1].to_vec()).is_err());
+
+        let mut fewer_components = solution.clone();
+        fewer_components[1].pop();
+        assert!(checkpoint.check_layout(2, &fewer_components).is_err());
+
+        let mut more_nodes = solution.clone();
+        more_nodes[0][1] = vector![1., 2., 3., 4.];
+        assert!(checkpoint.check_layout(2, &more_nodes).is_err());
+
+        assert!(checkpoint.check_time_step(0.3 / 17.).is_ok());
+        assert!(checkpoint.check_time_step(0.3 / 18.).is_err());
+
+        let mut short_residuals = checkpoint.clone();
+        short_residuals.residuals.pop();
+        assert!(short_residuals.check_layout(2, &solution).is_err());
+    }
+
+    #[test]
+    fn test_rejects_impossible_counts() {
+        let mut bytes = vec![];
+        checkpoint().write(&mut bytes).expect("could not write the checkpoint");
+        // The number of elements, then the number of components and of nodes.
+        let offset = bytes.len() - 2 * 2 * 2 * 3 * 8 - 1 - 3 * 4;
+        for &(field, count) in [(0, u32::MAX), (1, u32::MAX), (2, u32::MAX), (1, 0)].iter() {
+            let mut corrupt = bytes.clone();
+            let at = offset + 4 * field;
+            corrupt[at..at + 4].copy_from_slice(&[
+                count as u8,
+                (count >> 8) as u8,
+                (count >> 16) as u8,
+                (count >> 24) as u8,
+            ]);
+            let error = Checkpoint::read(&mut Cursor::new(corrupt)).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let schedule = CheckpointSchedule::new(Path::new("run.chk"), 3);
+        let due: Vec<i32> = (1..10).filter(|&steps| schedule.is_due(steps)).collect();
+        assert_eq!(due, vec![3, 6, 9]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_schedule_without_interval() {
+        CheckpointSchedule::new(Path::new("run.chk"), 0);
+    }
+}
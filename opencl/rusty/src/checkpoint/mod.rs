@@ -0,0 +1,4 @@
+/**
+Module for saving the state of a run to disk, so that it can be resumed later.
+ */
+pub mod file;
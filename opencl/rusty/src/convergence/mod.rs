@@ -0,0 +1,4 @@
+/**
+Module for measuring the order of accuracy of a scheme under h- and p-refinement.
+ */
+pub mod table;
@@ -0,0 +1,155 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+// The errors measured in one run of a convergence study.
+#[derive(Clone, Copy, Debug)]
+pub struct ConvergenceRow {
+    // The polynomial order
+    pub n: i32,
+    // The number of elements
+    pub n_k: usize,
+    // The element size
+    pub h: f64,
+
+    pub l2: f64,
+    pub l_inf: f64,
+    pub h1_seminorm: f64,
+
+    // The observed orders of convergence against the previous run with the same polynomial
+    // order, log(e_prev / e) / log(h_prev / h). These are None for the coarsest run.
+    pub l2_rate: Option<f64>,
+    pub l_inf_rate: Option<f64>,
+    pub h1_seminorm_rate: Option<f64>,
+}
+
+// The results of a convergence study, one row per run in the order they were added.
+#[derive(Debug)]
+pub struct ConvergenceTable {
+    pub rows: Vec<ConvergenceRow>,
+}
+
+impl ConvergenceTable {
+    pub fn new() -> ConvergenceTable {
+        ConvergenceTable { rows: vec![] }
+    }
+
+    // Record a run, computing its rates against the last run with the same order.
+    pub fn push(&mut self, n: i32, n_k: usize, h: f64, l2: f64, l_inf: f64, h1_seminorm: f64) {
+        let previous = self.rows.iter().rev().find(|row| row.n == n).cloned();
+        let (l2_rate, l_inf_rate, h1_seminorm_rate) = match previous {
+            Some(previous) => {
+                let rate = |e_prev: f64, e: f64| Some((e_prev / e).ln() / (previous.h / h).ln());
+                (
+                    rate(previous.l2, l2),
+                    rate(previous.l_inf, l_inf),
+                    rate(previous.h1_seminorm, h1_seminorm),
+                )
+            }
+            None => (None, None, None),
+        };
+        self.rows.push(ConvergenceRow {
+            n,
+            n_k,
+            h,
+            l2,
+            l_inf,
+            h1_seminorm,
+            l2_rate,
+            l_inf_rate,
+            h1_seminorm_rate,
+        });
+    }
+
+    // The rows for a single polynomial order, coarsest first.
+    pub fn order(&self, n: i32) -> Vec<&ConvergenceRow> {
+        self.rows.iter().filter(|row| row.n == n).collect()
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "n,n_k,h,l2,l2_rate,l_inf,l_inf_rate,h1_seminorm,h1_seminorm_rate"
+        )?;
+        let rate = |rate: Option<f64>| rate.map(|r| r.to_string()).unwrap_or_default();
+        for row in self.rows.iter() {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{}",
+                row.n,
+                row.n_k,
+                row.h,
+                row.l2,
+                rate(row.l2_rate),
+                row.l_inf,
+                rate(row.l_inf_rate),
+                row.h1_seminorm,
+                rate(row.h1_seminorm_rate),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn save_csv(&self, path: &Path) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        self.write_csv(&mut file)
+    }
+}
+
+impl fmt::Display for ConvergenceTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>3} {:>6} {:>10} {:>10} {:>6} {:>10} {:>6} {:>10} {:>6}",
+            "n", "n_k", "h", "L2", "rate", "Linf", "rate", "H1", "rate"
+        )?;
+        let rate = |rate: Option<f64>| rate.map(|r| format!("{:.2}", r)).unwrap_or_default();
+        for row in self.rows.iter() {
+            writeln!(
+                f,
+                "{:>3} {:>6} {:>10.3e} {:>10.3e} {:>6} {:>10.3e} {:>6} {:>10.3e} {:>6}",
+                row.n,
+                row.n_k,
+                row.h,
+                row.l2,
+                rate(row.l2_rate),
+                row.l_inf,
+                rate(row.l_inf_rate),
+                row.h1_seminorm,
+                rate(row.h1_seminorm_rate),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConvergenceTable;
+
+    #[test]
+    fn test_rates_and_csv() {
+        let mut table = ConvergenceTable::new();
+        table.push(1, 4, 0.5, 1e-2, 2e-2, 1e-1);
+        table.push(2, 4, 0.5, 1e-3, 2e-3, 1e-2);
+        table.push(1, 8, 0.25, 2.5e-3, 5e-3, 5e-2);
+        table.push(2, 8, 0.25, 1.25e-4, 2.5e-4, 2.5e-3);
+
+        let order_1 = table.order(1);
+        assert!(order_1[0].l2_rate.is_none());
+        assert!((order_1[1].l2_rate.unwrap() - 2.).abs() < 1e-12);
+        assert!((order_1[1].h1_seminorm_rate.unwrap() - 1.).abs() < 1e-12);
+        let order_2 = table.order(2);
+        assert!((order_2[1].l_inf_rate.unwrap() - 3.).abs() < 1e-12);
+
+        let mut csv: Vec<u8> = vec![];
+        table.write_csv(&mut csv).expect("writing to a Vec cannot fail");
+        let csv = String::from_utf8(csv).expect("the CSV is ASCII");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], "1,4,0.5,0.01,,0.02,,0.1,");
+        assert_eq!(lines[3].split(',').count(), 9);
+    }
+}
@@ -0,0 +1,232 @@
+use distmesh::mesh::{Point2D, Triangle};
+use std::collections::{HashMap, HashSet};
+
+// The Delaunay triangulation of `points`, by the Bowyer-Watson algorithm: the points are
+// added one at a time, each replacing the triangles whose circumcircles contain it by a fan of
+// triangles joining it to the boundary of the hole they leave. The triangles are listed
+// counter-clockwise. A point which repeats an earlier one is left out.
+pub fn delaunay(points: &[Point2D]) -> Vec<Triangle> {
+    let n = points.len();
+    let (mut x0, mut y0) = (f64::INFINITY, f64::INFINITY);
+    let (mut x1, mut y1) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in points.iter() {
+        x0 = x0.min(p.x);
+        y0 = y0.min(p.y);
+        x1 = x1.max(p.x);
+        y1 = y1.max(p.y);
+    }
+    let size = (x1 - x0).max(y1 - y0);
+    if n < 3 || !(size > 0.) {
+        return vec![];
+    }
+
+    // Start from a triangle large enough that none of the points are near its edges, and
+    // remove it with everything attached to it at the end.
+    let (cx, cy) = ((x0 + x1) / 2., (y0 + y1) / 2.);
+    let mut vertices: Vec<(f64, f64)> = points.iter().map(|p| (p.x, p.y)).collect();
+    vertices.push((cx - 20. * size, cy - 10. * size));
+    vertices.push((cx + 20. * size, cy - 10. * size));
+    vertices.push((cx, cy + 20. * size));
+
+    let mut triangulation = Triangulation {
+        vertices,
+        triangles: vec![],
+        edges: HashMap::new(),
+        last: 0,
+    };
+    triangulation.add([n, n + 1, n + 2]);
+    for i in 0..n {
+        triangulation.insert(i);
+    }
+
+    triangulation
+        .triangles
+        .iter()
+        .filter_map(|&t| t)
+        .filter(|t| t.iter().all(|&v| v < n))
+        .map(|t| Triangle {
+            a: t[0] as i32,
+            b: t[1] as i32,
+            c: t[2] as i32,
+        })
+        .collect()
+}
+
+struct Triangulation {
+    vertices: Vec<(f64, f64)>,
+    // Triangles which have been removed are left as None.
+    triangles: Vec<Option<[usize; 3]>>,
+    // The triangle to the left of each directed edge.
+    edges: HashMap<(usize, usize), usize>,
+    // The triangle added most recently, where the search for the next point starts.
+    last: usize,
+}
+
+impl Triangulation {
+    fn add(&mut self, t: [usize; 3]) {
+        let k = self.triangles.len();
+        for i in 0..3 {
+            self.edges.insert((t[i], t[(i + 1) % 3]), k);
+        }
+        self.triangles.push(Some(t));
+        self.last = k;
+    }
+
+    fn remove(&mut self, k: usize) {
+        let t = self.triangles[k]
+            .take()
+            .expect("the triangle was already removed");
+        for i in 0..3 {
+            self.edges.remove(&(t[i], t[(i + 1) % 3]));
+        }
+    }
+
+    // The triangle across the edge from a to b, which is to the left of the edge from b to a.
+    fn neighbor(&self, a: usize, b: usize) -> Option<usize> {
+        self.edges.get(&(b, a)).cloned()
+    }
+
+    // A triangle containing vertex p, found by walking from the last triangle added towards
+    // it, always crossing an edge which separates the current triangle from p.
+    fn locate(&self, p: usize) -> Option<usize> {
+        let mut k = self.last;
+        for _ in 0..self.triangles.len() {
+            let t = self.triangles[k]?;
+            let outside = (0..3).find(|&i| self.orient(t[i], t[(i + 1) % 3], p) < 0.);
+            match outside {
+                None => return Some(k),
+                Some(i) => k = self.neighbor(t[i], t[(i + 1) % 3])?,
+            }
+        }
+        None
+    }
+
+    fn insert(&mut self, p: usize) {
+        let start = match self.locate(p) {
+            Some(k) => k,
+            None => return,
+        };
+        // Only a repeated point is not strictly inside the circumcircle of its triangle.
+        if !self.in_circumcircle(start, p) {
+            return;
+        }
+
+        // The hole is the connected set of triangles whose circumcircles contain p.
+        let mut hole: HashSet<usize> = HashSet::new();
+        hole.insert(start);
+        let mut stack = vec![start];
+        while let Some(k) = stack.pop() {
+            let t = self.triangles[k].expect("a removed triangle is in the hole");
+            for i in 0..3 {
+                if let Some(across) = self.neighbor(t[i], t[(i + 1) % 3]) {
+                    if !hole.contains(&across) && self.in_circumcircle(across, p) {
+                        hole.insert(across);
+                        stack.push(across);
+                    }
+                }
+            }
+        }
+
+        let mut boundary = vec![];
+        for &k in hole.iter() {
+            let t = self.triangles[k].expect("a removed triangle is in the hole");
+            for i in 0..3 {
+                let (a, b) = (t[i], t[(i + 1) % 3]);
+                match self.neighbor(a, b) {
+                    Some(across) if hole.contains(&across) => {}
+                    _ => boundary.push((a, b)),
+                }
+            }
+        }
+        for &k in hole.iter() {
+            self.remove(k);
+        }
+        for &(a, b) in boundary.iter() {
+            self.add([a, b, p]);
+        }
+    }
+
+    // Twice the signed area of the triangle abc, which is positive if it is counter-clockwise.
+    fn orient(&self, a: usize, b: usize, c: usize) -> f64 {
+        let (a, b, c) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+        (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+    }
+
+    // Whether vertex p is strictly inside the circumcircle of triangle k.
+    fn in_circumcircle(&self, k: usize, p: usize) -> bool {
+        let t = self.triangles[k].expect("the triangle was removed");
+        let d = self.vertices[p];
+        let [a, b, c] = [t[0], t[1], t[2]].map(|v| {
+            let (x, y) = self.vertices[v];
+            (x - d.0, y - d.1)
+        });
+        let lift = |v: (f64, f64)| v.0 * v.0 + v.1 * v.1;
+        let det = lift(a) * (b.0 * c.1 - c.0 * b.1)
+            + lift(b) * (c.0 * a.1 - a.0 * c.1)
+            + lift(c) * (a.0 * b.1 - b.0 * a.1);
+        det > 0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::delaunay;
+    use distmesh::mesh::Point2D;
+
+    fn circumcircle_contains(points: &[Point2D], t: [i32; 3], p: &Point2D) -> bool {
+        let [a, b, c] = t.map(|v| &points[v as usize]);
+        let d = 2. * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+        let lift = |q: &Point2D| q.x * q.x + q.y * q.y;
+        let ux = (lift(a) * (b.y - c.y) + lift(b) * (c.y - a.y) + lift(c) * (a.y - b.y)) / d;
+        let uy = (lift(a) * (c.x - b.x) + lift(b) * (a.x - c.x) + lift(c) * (b.x - a.x)) / d;
+        let r2 = (a.x - ux).powi(2) + (a.y - uy).powi(2);
+        (p.x - ux).powi(2) + (p.y - uy).powi(2) < r2 * (1. - 1e-9)
+    }
+
+    #[test]
+    fn test_square_with_center() {
+        let points = vec![
+            Point2D { x: 0., y: 0. },
+            Point2D { x: 1., y: 0. },
+            Point2D { x: 1., y: 1. },
+            Point2D { x: 0., y: 1. },
+            Point2D { x: 0.5, y: 0.5 },
+            Point2D { x: 0.5, y: 0.5 },
+        ];
+        let triangles = delaunay(&points);
+        assert_eq!(triangles.len(), 4);
+        assert!(triangles.iter().all(|t| t.a == 4 || t.b == 4 || t.c == 4));
+    }
+
+    #[test]
+    fn test_empty_circumcircles() {
+        // A scattering of points from a fixed sequence, so that the test is repeatable.
+        let points: Vec<Point2D> = (0..200)
+            .map(|i| {
+                let t = i as f64;
+                Point2D {
+                    x: (t * 0.618034).fract(),
+                    y: (t * 0.754878 + (t * 0.1).sin()).fract().abs(),
+                }
+            })
+            .collect();
+        let triangles = delaunay(&points);
+
+        let mut area = 0.;
+        for t in triangles.iter() {
+            let [a, b, c] = [t.a, t.b, t.c].map(|v| &points[v as usize]);
+            let signed_area = (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y);
+            assert!(signed_area > 0., "the triangles must be counter-clockwise");
+            area += signed_area / 2.;
+            for p in points.iter() {
+                assert!(!circumcircle_contains(&points, [t.a, t.b, t.c], p));
+            }
+        }
+        // Every point is a vertex of some triangle.
+        let used: Vec<bool> = (0..points.len() as i32)
+            .map(|v| triangles.iter().any(|t| t.a == v || t.b == v || t.c == v))
+            .collect();
+        assert!(used.iter().all(|&u| u));
+        assert!(area > 0.5 && area <= 1.);
+    }
+}
@@ -0,0 +1,73 @@
+// Signed distance functions, which describe a region by the distance from each point to its
+// boundary, negative inside the region and positive outside it. The combinators are exact
+// away from the places where the boundaries of their arguments meet, which is all that
+// `generator::generate` needs.
+
+// The disk of radius r centered at (cx, cy).
+pub fn circle(cx: f64, cy: f64, r: f64) -> impl Fn(f64, f64) -> f64 {
+    move |x, y| ((x - cx).powi(2) + (y - cy).powi(2)).sqrt() - r
+}
+
+// The rectangle [x0, x1] × [y0, y1].
+pub fn rectangle(x0: f64, x1: f64, y0: f64, y1: f64) -> impl Fn(f64, f64) -> f64 {
+    move |x, y| {
+        let dx = (x0 - x).max(x - x1);
+        let dy = (y0 - y).max(y - y1);
+        // Outside a corner, the nearest point of the rectangle is the corner itself.
+        let outside = (dx.max(0.).powi(2) + dy.max(0.).powi(2)).sqrt();
+        outside + dx.max(dy).min(0.)
+    }
+}
+
+// The points in either region.
+pub fn union<F, G>(f: F, g: G) -> impl Fn(f64, f64) -> f64
+where
+    F: Fn(f64, f64) -> f64,
+    G: Fn(f64, f64) -> f64,
+{
+    move |x, y| f(x, y).min(g(x, y))
+}
+
+// The points in the region of f but not that of g.
+pub fn difference<F, G>(f: F, g: G) -> impl Fn(f64, f64) -> f64
+where
+    F: Fn(f64, f64) -> f64,
+    G: Fn(f64, f64) -> f64,
+{
+    move |x, y| f(x, y).max(-g(x, y))
+}
+
+// The points in both regions.
+pub fn intersection<F, G>(f: F, g: G) -> impl Fn(f64, f64) -> f64
+where
+    F: Fn(f64, f64) -> f64,
+    G: Fn(f64, f64) -> f64,
+{
+    move |x, y| f(x, y).max(g(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{circle, difference, intersection, rectangle, union};
+
+    #[test]
+    fn test_distances() {
+        let disk = circle(1., 0., 2.);
+        assert_eq!(disk(1., 0.), -2.);
+        assert_eq!(disk(4., 4.), 3.);
+
+        let square = rectangle(0., 1., 0., 1.);
+        assert_eq!(square(0.5, 0.75), -0.25);
+        assert_eq!(square(0.5, 3.), 2.);
+        assert_eq!(square(4., 5.), 5.);
+
+        let unit_disk = || circle(0., 0., 1.);
+        let corner = || rectangle(0., 2., 0., 2.);
+        assert_eq!(union(unit_disk(), corner())(1.5, 1.5), -0.5);
+        assert_eq!(union(unit_disk(), corner())(-0.5, 0.), -0.5);
+        assert_eq!(difference(corner(), unit_disk())(0.25, 0.), 0.75);
+        assert_eq!(difference(corner(), unit_disk())(1.5, 0.5), -0.5);
+        assert_eq!(intersection(corner(), unit_disk())(0.5, 0.), 0.);
+        assert_eq!(intersection(corner(), unit_disk())(-0.5, 0.), 0.5);
+    }
+}
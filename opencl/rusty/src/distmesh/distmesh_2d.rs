@@ -1,58 +1,214 @@
 use distmesh::mesh::Mesh;
+use distmesh::mesh::MeshError;
 use distmesh::mesh::Point2D;
 use distmesh::mesh::Triangle;
-use std::num::ParseFloatError;
-use std::num::ParseIntError;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::str::FromStr;
 
 pub fn ellipse() -> Mesh {
-    parse_distmesh_2d(
+    bundled_mesh(
+        "ellipse",
         include_str!("../../static/meshes_2d/ellipse_points"),
         include_str!("../../static/meshes_2d/ellipse_triangles"),
     )
 }
 
 pub fn unit_square() -> Mesh {
-    parse_distmesh_2d(
+    bundled_mesh(
+        "unit_square",
         include_str!("../../static/meshes_2d/unit_square_points"),
         include_str!("../../static/meshes_2d/unit_square_triangles"),
     )
 }
 
-pub fn parse_distmesh_2d(points_file: &str, triangles_file: &str) -> Mesh {
-    let points = points_file
-        .split("\n")
-        .map(|line: &str| line.parse::<Point2D>().expect("error parsing point: "))
-        .collect();
-    let triangles = triangles_file
-        .split("\n")
-        .map(|line: &str| line.parse::<Triangle>().expect("error parsing triangle: "))
-        .collect();
+// One of the meshes in static/meshes_2d, which are compiled in so that they are there
+// wherever the program is run from. The paths only name the files in error messages.
+fn bundled_mesh(name: &str, points_file: &str, triangles_file: &str) -> Mesh {
+    let dir = Path::new("static/meshes_2d");
+    parse_distmesh_2d(
+        &dir.join(format!("{}_points", name)),
+        points_file,
+        &dir.join(format!("{}_triangles", name)),
+        triangles_file,
+    )
+    .unwrap_or_else(|e| panic!("could not load the bundled mesh: {}", e))
+}
+
+// Read the output of the Matlab DistMesh program: a file of points with an x and a y
+// coordinate on each line, and a file of triangles with three 1-based indices of points on
+// each line. Fields may be separated by tabs or spaces, and blank lines and lines starting
+// with # or % are skipped.
+pub fn load_distmesh_2d(points_path: &Path, triangles_path: &Path) -> Result<Mesh, MeshError> {
+    let points_file = read(points_path)?;
+    let triangles_file = read(triangles_path)?;
+    parse_distmesh_2d(points_path, &points_file, triangles_path, &triangles_file)
+}
+
+// As `load_distmesh_2d`, for the contents of the two files, which were read from the paths
+// given.
+fn parse_distmesh_2d(
+    points_path: &Path,
+    points_file: &str,
+    triangles_path: &Path,
+    triangles_file: &str,
+) -> Result<Mesh, MeshError> {
+    let points = parse_points(points_path, points_file)?;
+    let triangles = parse_triangles(triangles_path, triangles_file, points.len())?;
+    Ok(Mesh {
+        points,
+        triangles,
+        boundary_edges: vec![],
+    })
+}
+
+fn read(path: &Path) -> Result<String, MeshError> {
+    fs::read_to_string(path).map_err(|e| MeshError::io(path, e))
+}
+
+// The points listed in `points_file`, which was read from `path`.
+pub fn parse_points(path: &Path, points_file: &str) -> Result<Vec<Point2D>, MeshError> {
+    records(points_file)
+        .map(|(line, fields)| {
+            let coords = parse_fields::<f64>(path, line, &fields, 2, "coordinates")?;
+            Ok(Point2D {
+                x: coords[0],
+                y: coords[1],
+            })
+        })
+        .collect()
+}
 
-    Mesh { points, triangles }
+// The triangles listed in `triangles_file`, which was read from `path`. Each must refer to
+// one of the first `n_points` points.
+pub fn parse_triangles(
+    path: &Path,
+    triangles_file: &str,
+    n_points: usize,
+) -> Result<Vec<Triangle>, MeshError> {
+    records(triangles_file)
+        .map(|(line, fields)| {
+            let indices = parse_fields::<i32>(path, line, &fields, 3, "indices")?;
+            for &i in indices.iter() {
+                if i < 1 || i as usize > n_points {
+                    return Err(MeshError::parse(
+                        path,
+                        line,
+                        format!("index {} is not between 1 and {}", i, n_points),
+                    ));
+                }
+            }
+            // subtract 1 because Matlab is 1-indexed
+            Ok(Triangle {
+                a: indices[0] - 1,
+                b: indices[1] - 1,
+                c: indices[2] - 1,
+            })
+        })
+        .collect()
 }
 
-impl FromStr for Point2D {
-    type Err = ParseFloatError;
+// The line number and the whitespace-separated fields of each line of `file` which is not
+// blank or a comment.
+pub fn records<'a>(file: &'a str) -> impl Iterator<Item = (usize, Vec<&'a str>)> + 'a {
+    file.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|&(_, line)| !(line.is_empty() || line.starts_with('#') || line.starts_with('%')))
+        .map(|(line, text)| (line, text.split_whitespace().collect()))
+}
 
-    // from a tab-separated pair of floats
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords: Vec<&str> = s.split("\t").collect();
-        let x = coords[0].parse::<f64>()?;
-        let y = coords[1].parse::<f64>()?;
-        Ok(Point2D { x, y })
+// Parse exactly `n` fields as numbers of type T. `what` names them in error messages.
+pub fn parse_fields<T>(
+    path: &Path,
+    line: usize,
+    fields: &[&str],
+    n: usize,
+    what: &str,
+) -> Result<Vec<T>, MeshError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    if fields.len() != n {
+        return Err(MeshError::parse(
+            path,
+            line,
+            format!("expected {} {}, found {}", n, what, fields.len()),
+        ));
     }
+    fields
+        .iter()
+        .map(|field| {
+            field.parse::<T>().map_err(|e| {
+                MeshError::parse(path, line, format!("could not parse {:?}: {}", field, e))
+            })
+        })
+        .collect()
 }
 
-impl FromStr for Triangle {
-    type Err = ParseIntError;
+#[cfg(test)]
+mod tests {
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use super::{ellipse, load_distmesh_2d, parse_points, parse_triangles, unit_square};
+    use distmesh::mesh::MeshError;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_blank_lines_and_comments() {
+        let path = Path::new("points");
+        let points = parse_points(path, "% x\ty\n0\t0\n\n# the far corner\n1 1.5\n")
+            .expect("could not parse the points");
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[1].x, points[1].y), (1., 1.5));
+
+        let triangles = parse_triangles(path, "1\t2\t3\n3 2 1\n\n", 3)
+            .expect("could not parse the triangles");
+        assert_eq!((triangles[1].a, triangles[1].b, triangles[1].c), (2, 1, 0));
+    }
+
+    #[test]
+    fn test_errors_have_line_numbers() {
+        let path = Path::new("mesh/points");
+        match parse_points(path, "0\t0\n\n1\tone\n") {
+            Err(MeshError::Parse { line, .. }) => assert_eq!(line, 3),
+            _ => panic!("expected a parse error"),
+        }
+        let error = parse_points(path, "0\t0\t0\n").err().expect("expected an error");
+        assert_eq!(format!("{}", error), "mesh/points:1: expected 2 coordinates, found 3");
+
+        match parse_triangles(path, "1 2 3\n1 2 4\n", 3) {
+            Err(MeshError::Parse { line, .. }) => assert_eq!(line, 2),
+            _ => panic!("expected a parse error"),
+        }
+        assert!(parse_triangles(path, "0 1 2\n", 3).is_err());
+    }
+
+    #[test]
+    fn test_load_distmesh_2d() {
+        let dir = tempdir().expect("could not create a temporary directory");
+        let (points, triangles) = (dir.path().join("points"), dir.path().join("triangles"));
+        fs::write(&points, "0\t0\n1\t0\n0\t1\n").expect("could not write the points");
+        fs::write(&triangles, "1\t2\t3\n").expect("could not write the triangles");
+        let mesh = load_distmesh_2d(&points, &triangles).expect("could not load the mesh");
+        assert_eq!(mesh.points.len(), 3);
+        assert_eq!(mesh.triangles.len(), 1);
+
+        match load_distmesh_2d(&dir.path().join("missing"), &triangles) {
+            Err(MeshError::Io { path, .. }) => assert_eq!(path, dir.path().join("missing")),
+            _ => panic!("expected an I/O error"),
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords: Vec<&str> = s.split("\t").collect();
-        // subtract 1 because Matlab is 1-indexed
-        let a = coords[0].parse::<i32>()? - 1;
-        let b = coords[1].parse::<i32>()? - 1;
-        let c = coords[2].parse::<i32>()? - 1;
-        Ok(Triangle { a, b, c })
+    #[test]
+    fn test_bundled_meshes() {
+        for mesh in [unit_square(), ellipse()].iter() {
+            assert!(mesh.triangles.len() > 0);
+            assert!(mesh.triangles.iter().all(|t| t.a >= 0 && t.b >= 0 && t.c >= 0));
+        }
     }
 }
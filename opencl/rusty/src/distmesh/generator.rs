@@ -0,0 +1,267 @@
+use distmesh::delaunay::delaunay;
+use distmesh::mesh::{counter_clockwise, Mesh, Point2D};
+use std::f64;
+
+// The constants of the Matlab DistMesh program. The iteration stops once no interior point
+// moves further than DPTOL * h0 in a step, and the points are retriangulated whenever one has
+// moved TTOL * h0 since the last triangulation. The bars are FSCALE times longer than the
+// ideal lengths in the absence of the boundary, so that they push the points outwards.
+const DPTOL: f64 = 0.001;
+const TTOL: f64 = 0.1;
+const FSCALE: f64 = 1.2;
+const DELTAT: f64 = 0.2;
+
+// The iteration sometimes creeps along without ever meeting DPTOL, so it gives up here.
+const MAX_ITERATIONS: usize = 5000;
+
+// The element size function for a mesh of uniform elements.
+pub fn uniform_size(_x: f64, _y: f64) -> f64 {
+    1.
+}
+
+// Mesh the region where the signed distance function `fd` is negative, by Persson and
+// Strang's DistMesh algorithm. The points start on an equilateral grid of spacing h0 over the
+// bounding box `bbox`, given by its lower left and upper right corners, and are thinned out
+// where the relative element size `fh` is large. Then each edge of their Delaunay triangulation
+// is treated as a spring pushing its ends apart, and the points move until the forces
+// balance, with those which leave the region projected back onto its boundary. The points in
+// `fixed`, such as the corners of the region, do not move.
+//
+// The mesh is the same each time it is generated from the same arguments. Its boundary edges
+// are not tagged.
+pub fn generate<D, H>(fd: D, fh: H, h0: f64, bbox: [Point2D; 2], fixed: &[Point2D]) -> Mesh
+where
+    D: Fn(f64, f64) -> f64,
+    H: Fn(f64, f64) -> f64,
+{
+    let geps = 0.001 * h0;
+    let deps = f64::EPSILON.sqrt() * h0;
+
+    let mut p: Vec<Point2D> = fixed.iter().map(|q| Point2D { x: q.x, y: q.y }).collect();
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let candidates: Vec<Point2D> = initial_points(h0, &bbox)
+        .into_iter()
+        .filter(|q| fd(q.x, q.y) < geps)
+        .filter(|q| !fixed.iter().any(|f| (f.x, f.y) == (q.x, q.y)))
+        .collect();
+    // Keep each point with probability proportional to the density of points fh asks for.
+    let density: Vec<f64> = candidates.iter().map(|q| fh(q.x, q.y).powi(-2)).collect();
+    let max_density = density.iter().cloned().fold(0., f64::max);
+    for (q, r) in candidates.into_iter().zip(density.into_iter()) {
+        if rng.next_f64() < r / max_density {
+            p.push(q);
+        }
+    }
+    let n_fixed = fixed.len();
+
+    let mut triangles = vec![];
+    let mut bars: Vec<(usize, usize)> = vec![];
+    let mut last_triangulated: Vec<(f64, f64)> = vec![];
+    for _ in 0..MAX_ITERATIONS {
+        let moved = last_triangulated.len() != p.len()
+            || p.iter()
+                .zip(last_triangulated.iter())
+                .any(|(q, &(x, y))| ((q.x - x).powi(2) + (q.y - y).powi(2)).sqrt() > TTOL * h0);
+        if moved {
+            last_triangulated = p.iter().map(|q| (q.x, q.y)).collect();
+            triangles = delaunay(&p)
+                .into_iter()
+                .filter(|t| {
+                    let (a, b, c) = (&p[t.a as usize], &p[t.b as usize], &p[t.c as usize]);
+                    fd((a.x + b.x + c.x) / 3., (a.y + b.y + c.y) / 3.) < -geps
+                })
+                .collect();
+            bars = triangles
+                .iter()
+                .flat_map(|t| vec![(t.a, t.b), (t.b, t.c), (t.c, t.a)])
+                .map(|(a, b)| (a.min(b) as usize, a.max(b) as usize))
+                .collect();
+            bars.sort();
+            bars.dedup();
+        }
+
+        // The spring force along each bar, which only ever pushes its ends apart.
+        let lengths: Vec<f64> = bars
+            .iter()
+            .map(|&(i, j)| ((p[i].x - p[j].x).powi(2) + (p[i].y - p[j].y).powi(2)).sqrt())
+            .collect();
+        let sizes: Vec<f64> = bars
+            .iter()
+            .map(|&(i, j)| fh((p[i].x + p[j].x) / 2., (p[i].y + p[j].y) / 2.))
+            .collect();
+        let scale = FSCALE
+            * (lengths.iter().map(|l| l * l).sum::<f64>()
+                / sizes.iter().map(|h| h * h).sum::<f64>())
+            .sqrt();
+        let mut force = vec![(0., 0.); p.len()];
+        for ((&(i, j), &length), &size) in bars.iter().zip(lengths.iter()).zip(sizes.iter()) {
+            let f = (size * scale - length).max(0.) / length;
+            let (fx, fy) = (f * (p[i].x - p[j].x), f * (p[i].y - p[j].y));
+            force[i] = (force[i].0 + fx, force[i].1 + fy);
+            force[j] = (force[j].0 - fx, force[j].1 - fy);
+        }
+        for f in force.iter_mut().take(n_fixed) {
+            *f = (0., 0.);
+        }
+
+        let mut max_step: f64 = 0.;
+        for (q, f) in p.iter_mut().zip(force.iter()) {
+            q.x += DELTAT * f.0;
+            q.y += DELTAT * f.1;
+
+            let d = fd(q.x, q.y);
+            if d > 0. {
+                let dx = (fd(q.x + deps, q.y) - d) / deps;
+                let dy = (fd(q.x, q.y + deps) - d) / deps;
+                let norm2 = dx * dx + dy * dy;
+                q.x -= d * dx / norm2;
+                q.y -= d * dy / norm2;
+            } else if d < -geps {
+                max_step = max_step.max(DELTAT * (f.0 * f.0 + f.1 * f.1).sqrt());
+            }
+        }
+        if max_step / h0 < DPTOL {
+            break;
+        }
+    }
+
+    // Leave out any points which ended up outside every triangle, keeping the others in order.
+    let mut used = vec![false; p.len()];
+    for t in triangles.iter() {
+        for &v in [t.a, t.b, t.c].iter() {
+            used[v as usize] = true;
+        }
+    }
+    let mut index = vec![-1; p.len()];
+    let mut points = vec![];
+    for (i, q) in p.iter().enumerate().filter(|&(i, _)| used[i]) {
+        index[i] = points.len() as i32;
+        points.push(Point2D { x: q.x, y: q.y });
+    }
+    let triangles = triangles
+        .iter()
+        .map(|t| {
+            let corners = [
+                index[t.a as usize],
+                index[t.b as usize],
+                index[t.c as usize],
+            ];
+            counter_clockwise(&points, &corners)
+        })
+        .collect();
+    Mesh {
+        points,
+        triangles,
+        boundary_edges: vec![],
+    }
+}
+
+// An equilateral grid of points of spacing h0 filling the bounding box, row by row from the
+// bottom, with every other row shifted right by half a spacing.
+fn initial_points(h0: f64, bbox: &[Point2D; 2]) -> Vec<Point2D> {
+    let dy = h0 * 3f64.sqrt() / 2.;
+    let n_x = ((bbox[1].x - bbox[0].x) / h0 + 1e-10).floor() as usize + 1;
+    let n_y = ((bbox[1].y - bbox[0].y) / dy + 1e-10).floor() as usize + 1;
+    let mut points = Vec::with_capacity(n_x * n_y);
+    for j in 0..n_y {
+        let shift = if j % 2 == 1 { h0 / 2. } else { 0. };
+        for i in 0..n_x {
+            points.push(Point2D {
+                x: bbox[0].x + i as f64 * h0 + shift,
+                y: bbox[0].y + j as f64 * dy,
+            });
+        }
+    }
+    points
+}
+
+// A xorshift generator, so that the points thinned out are the same on every run.
+struct Rng(u64);
+
+impl Rng {
+    // A number uniformly distributed in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, uniform_size};
+    use distmesh::distance::{circle, difference, rectangle};
+    use distmesh::mesh::{Mesh, Point2D};
+    use std::f64::consts;
+
+    // The total area of the mesh and the smallest ratio of twice the inradius to the
+    // circumradius of its triangles, which is 1 for an equilateral triangle.
+    fn area_and_quality(mesh: &Mesh) -> (f64, f64) {
+        let mut area = 0.;
+        let mut quality: f64 = 1.;
+        for t in mesh.triangles.iter() {
+            let (a, b, c) = (
+                &mesh.points[t.a as usize],
+                &mesh.points[t.b as usize],
+                &mesh.points[t.c as usize],
+            );
+            let signed_area = ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)) / 2.;
+            assert!(signed_area > 0., "the triangles must be counter-clockwise");
+            area += signed_area;
+            let length =
+                |p: &Point2D, q: &Point2D| ((p.x - q.x).powi(2) + (p.y - q.y).powi(2)).sqrt();
+            let (ab, bc, ca) = (length(a, b), length(b, c), length(c, a));
+            let s = (ab + bc + ca) / 2.;
+            quality = quality.min(4. * signed_area * signed_area / (s * ab * bc * ca / 2.));
+        }
+        (area, quality)
+    }
+
+    #[test]
+    fn test_unit_disk() {
+        let bbox = [Point2D { x: -1., y: -1. }, Point2D { x: 1., y: 1. }];
+        let mesh = generate(circle(0., 0., 1.), uniform_size, 0.2, bbox, &[]);
+        let (area, quality) = area_and_quality(&mesh);
+        assert!(
+            (area - consts::PI).abs() < 0.05 * consts::PI,
+            "area {}",
+            area
+        );
+        assert!(quality > 0.5, "quality {}", quality);
+        assert!(mesh.points.iter().all(|p| p.x.hypot(p.y) < 1. + 1e-3));
+    }
+
+    #[test]
+    fn test_square_with_hole() {
+        let fd = difference(rectangle(-1., 1., -1., 1.), circle(0., 0., 0.5));
+        // Smaller elements near the hole.
+        let fh = |x: f64, y: f64| 0.05 + 0.3 * (x.hypot(y) - 0.5);
+        let bbox = [Point2D { x: -1., y: -1. }, Point2D { x: 1., y: 1. }];
+        let corners = [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)];
+        let fixed: Vec<Point2D> = corners.iter().map(|&(x, y)| Point2D { x, y }).collect();
+        let mesh = generate(fd, fh, 0.05, bbox, &fixed);
+
+        let (area, quality) = area_and_quality(&mesh);
+        let exact = 4. - consts::PI / 4.;
+        assert!((area - exact).abs() < 0.01 * exact, "area {}", area);
+        assert!(quality > 0.4, "quality {}", quality);
+        // The corners stay put, as the first points of the mesh.
+        assert_eq!((mesh.points[0].x, mesh.points[0].y), (-1., -1.));
+        assert_eq!((mesh.points[2].x, mesh.points[2].y), (1., 1.));
+
+        let again = generate(
+            difference(rectangle(-1., 1., -1., 1.), circle(0., 0., 0.5)),
+            fh,
+            0.05,
+            [Point2D { x: -1., y: -1. }, Point2D { x: 1., y: 1. }],
+            &fixed,
+        );
+        assert_eq!(again.points.len(), mesh.points.len());
+        assert!(again
+            .points
+            .iter()
+            .zip(mesh.points.iter())
+            .all(|(p, q)| (p.x, p.y) == (q.x, q.y)));
+    }
+}
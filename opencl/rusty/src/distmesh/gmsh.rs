@@ -0,0 +1,507 @@
+use distmesh::distmesh_2d::records;
+use distmesh::mesh::{counter_clockwise, BoundaryEdge, Mesh, MeshError, Point2D};
+use distmesh::reader::Reader;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Gmsh element types
+const LINE: i32 = 1;
+const TRIANGLE: i32 = 2;
+const POINT: i32 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Version {
+    V2,
+    V4,
+}
+
+// Read a 2D mesh from a Gmsh .msh file in the MSH 2.2 or 4.1 ASCII format. The triangles
+// make up the mesh, and each line element becomes a boundary edge tagged with its physical
+// group, or 0 if it has none. Point elements are ignored, and any other kind of element is
+// an error.
+pub fn load_gmsh(path: &Path) -> Result<Mesh, MeshError> {
+    let file = fs::read_to_string(path).map_err(|e| MeshError::io(path, e))?;
+    parse_gmsh(path, &file)
+}
+
+// The mesh in `file`, which was read from `path`.
+pub fn parse_gmsh(path: &Path, file: &str) -> Result<Mesh, MeshError> {
+    let mut reader = Reader::new(path, records(file).collect());
+
+    let mut version = None;
+    // The physical group of each curve entity, from the MSH 4 $Entities section.
+    let mut curve_groups: HashMap<i32, i32> = HashMap::new();
+    // The index in `points` of each node tag.
+    let mut node_indices: HashMap<i32, i32> = HashMap::new();
+    let mut points: Vec<Point2D> = vec![];
+    let mut triangles = vec![];
+    let mut boundary_edges: Vec<BoundaryEdge> = vec![];
+
+    while let Some((line, fields)) = reader.next_record() {
+        let section = fields[0];
+        if !section.starts_with('$') {
+            return Err(reader.error(line, format!("expected a section, found {:?}", section)));
+        }
+        if section != "$MeshFormat" && version.is_none() {
+            return Err(reader.error(line, "expected $MeshFormat first".to_string()));
+        }
+        match section {
+            "$MeshFormat" => {
+                version = Some(read_mesh_format(&mut reader)?);
+            }
+            "$Entities" if version == Some(Version::V4) => {
+                curve_groups = read_entities(&mut reader)?;
+            }
+            "$Nodes" => {
+                let nodes = match version {
+                    Some(Version::V2) => read_nodes_v2(&mut reader)?,
+                    _ => read_nodes_v4(&mut reader)?,
+                };
+                for (tag, point) in nodes.into_iter() {
+                    node_indices.insert(tag, points.len() as i32);
+                    points.push(point);
+                }
+            }
+            "$Elements" => {
+                let elements = match version {
+                    Some(Version::V2) => read_elements_v2(&mut reader)?,
+                    _ => read_elements_v4(&mut reader, &curve_groups)?,
+                };
+                for element in elements.into_iter() {
+                    let mut indices = vec![];
+                    for tag in element.nodes.iter() {
+                        match node_indices.get(tag) {
+                            Some(&i) => indices.push(i),
+                            None => {
+                                let message = format!("no node with tag {}", tag);
+                                return Err(reader.error(element.line, message));
+                            }
+                        }
+                    }
+                    match element.element_type {
+                        LINE => boundary_edges.push(BoundaryEdge {
+                            a: indices[0],
+                            b: indices[1],
+                            tag: element.physical_group,
+                        }),
+                        TRIANGLE => {
+                            triangles.push(counter_clockwise(&points, &indices));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {
+                skip_section(&mut reader, line, &section[1..])?;
+                continue;
+            }
+        }
+        end_section(&mut reader, &section[1..])?;
+    }
+
+    if version.is_none() {
+        return Err(reader.error(reader.line, "no $MeshFormat section".to_string()));
+    }
+    Ok(Mesh {
+        points,
+        triangles,
+        boundary_edges,
+    })
+}
+
+fn end_section(reader: &mut Reader, name: &str) -> Result<(), MeshError> {
+    let (line, fields) = reader.record()?;
+    let end = format!("$End{}", name);
+    if fields != [end.as_str()] {
+        return Err(reader.error(line, format!("expected {}", end)));
+    }
+    Ok(())
+}
+
+// Skip over a section this reader does not use, such as $PhysicalNames.
+fn skip_section(reader: &mut Reader, start: usize, name: &str) -> Result<(), MeshError> {
+    let end = format!("$End{}", name);
+    while let Some((_, fields)) = reader.next_record() {
+        if fields[0] == end {
+            return Ok(());
+        }
+    }
+    Err(reader.error(start, format!("${} has no {}", name, end)))
+}
+
+struct Element {
+    // The line it was read from
+    line: usize,
+    element_type: i32,
+    physical_group: i32,
+    nodes: Vec<i32>,
+}
+
+// The number of nodes of each supported element type.
+fn n_nodes(reader: &Reader, line: usize, element_type: i32) -> Result<usize, MeshError> {
+    match element_type {
+        LINE => Ok(2),
+        TRIANGLE => Ok(3),
+        POINT => Ok(1),
+        _ => Err(reader.error(line, format!("element type {} is not supported", element_type))),
+    }
+}
+
+fn read_mesh_format(reader: &mut Reader) -> Result<Version, MeshError> {
+    let (line, fields) = reader.record()?;
+    if fields.len() != 3 {
+        return Err(reader.error(line, "expected version, file type and data size".to_string()));
+    }
+    let version = match fields[0] {
+        "2" | "2.0" | "2.1" | "2.2" => Version::V2,
+        "4.1" => Version::V4,
+        other => {
+            return Err(reader.error(line, format!("MSH version {} is not supported", other)));
+        }
+    };
+    if fields[1] != "0" {
+        return Err(reader.error(line, "binary MSH files are not supported".to_string()));
+    }
+    Ok(version)
+}
+
+fn read_nodes_v2(reader: &mut Reader) -> Result<Vec<(i32, Point2D)>, MeshError> {
+    let n = reader.count()?;
+    let mut nodes = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (line, fields) = reader.record()?;
+        if fields.len() != 4 {
+            return Err(reader.error(line, "expected a tag and 3 coordinates".to_string()));
+        }
+        let tag = reader.parse::<i32>(line, fields[0])?;
+        let x = reader.parse::<f64>(line, fields[1])?;
+        let y = reader.parse::<f64>(line, fields[2])?;
+        nodes.push((tag, Point2D { x, y }));
+    }
+    Ok(nodes)
+}
+
+fn read_elements_v2(reader: &mut Reader) -> Result<Vec<Element>, MeshError> {
+    let n = reader.count()?;
+    let mut elements = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (line, fields) = reader.record()?;
+        let numbers = reader.parse_all::<i32>(line, &fields)?;
+        if numbers.len() < 3 {
+            return Err(reader.error(line, "expected a tag, a type and tags".to_string()));
+        }
+        if numbers[2] < 0 {
+            return Err(reader.error(line, "expected a non-negative number of tags".to_string()));
+        }
+        let (element_type, n_tags) = (numbers[1], numbers[2] as usize);
+        let n_nodes = n_nodes(reader, line, element_type)?;
+        if numbers.len() != 3 + n_tags + n_nodes {
+            let message = format!("expected {} tags and {} nodes", n_tags, n_nodes);
+            return Err(reader.error(line, message));
+        }
+        // The first tag is the physical group, and the second the elementary entity.
+        let physical_group = if n_tags > 0 { numbers[3] } else { 0 };
+        elements.push(Element {
+            line,
+            element_type,
+            physical_group,
+            nodes: numbers[3 + n_tags..].to_vec(),
+        });
+    }
+    Ok(elements)
+}
+
+// The physical group of each curve entity, where the curves without one get 0.
+fn read_entities(reader: &mut Reader) -> Result<HashMap<i32, i32>, MeshError> {
+    let (line, fields) = reader.record()?;
+    let counts = reader.parse_all::<usize>(line, &fields)?;
+    if counts.len() != 4 {
+        return Err(reader.error(line, "expected 4 entity counts".to_string()));
+    }
+
+    let mut curve_groups = HashMap::new();
+    for dimension in 0..4 {
+        for _ in 0..counts[dimension] {
+            let (line, fields) = reader.record()?;
+            // A point has its coordinates, and everything else its bounding box.
+            let n_coordinates = if dimension == 0 { 3 } else { 6 };
+            if fields.len() < n_coordinates + 2 {
+                return Err(reader.error(line, "truncated entity".to_string()));
+            }
+            let tag = reader.parse::<i32>(line, fields[0])?;
+            let n_groups = reader.parse::<usize>(line, fields[n_coordinates + 1])?;
+            let groups = &fields[n_coordinates + 2..];
+            if groups.len() < n_groups {
+                return Err(reader.error(line, "truncated entity".to_string()));
+            }
+            if dimension == 1 {
+                let group = match n_groups {
+                    0 => 0,
+                    _ => reader.parse::<i32>(line, groups[0])?.abs(),
+                };
+                curve_groups.insert(tag, group);
+            }
+        }
+    }
+    Ok(curve_groups)
+}
+
+fn read_nodes_v4(reader: &mut Reader) -> Result<Vec<(i32, Point2D)>, MeshError> {
+    let (line, fields) = reader.record()?;
+    let header = reader.parse_all::<usize>(line, &fields)?;
+    if header.len() != 4 {
+        return Err(reader.error(line, "expected 4 numbers in the $Nodes header".to_string()));
+    }
+    let n_blocks = reader.check_count(line, header[0])?;
+    let n_nodes = reader.check_count(line, header[1])?;
+
+    let mut nodes = Vec::with_capacity(n_nodes);
+    for _ in 0..n_blocks {
+        let (line, fields) = reader.record()?;
+        let block = reader.parse_all::<i32>(line, &fields)?;
+        if block.len() != 4 {
+            return Err(reader.error(line, "expected 4 numbers in a node block header".to_string()));
+        }
+        if block[2] != 0 {
+            return Err(reader.error(line, "parametric nodes are not supported".to_string()));
+        }
+        let n = reader.check_count(line, block[3] as usize)?;
+        let mut tags = Vec::with_capacity(n);
+        for _ in 0..n {
+            let (line, fields) = reader.record()?;
+            if fields.len() != 1 {
+                return Err(reader.error(line, "expected a node tag".to_string()));
+            }
+            tags.push(reader.parse::<i32>(line, fields[0])?);
+        }
+        for tag in tags.into_iter() {
+            let (line, fields) = reader.record()?;
+            if fields.len() != 3 {
+                return Err(reader.error(line, "expected 3 coordinates".to_string()));
+            }
+            let x = reader.parse::<f64>(line, fields[0])?;
+            let y = reader.parse::<f64>(line, fields[1])?;
+            nodes.push((tag, Point2D { x, y }));
+        }
+    }
+    Ok(nodes)
+}
+
+fn read_elements_v4(
+    reader: &mut Reader,
+    curve_groups: &HashMap<i32, i32>,
+) -> Result<Vec<Element>, MeshError> {
+    let (line, fields) = reader.record()?;
+    let header = reader.parse_all::<usize>(line, &fields)?;
+    if header.len() != 4 {
+        let message = "expected 4 numbers in the $Elements header".to_string();
+        return Err(reader.error(line, message));
+    }
+    let header_line = line;
+    let n_blocks = reader.check_count(line, header[0])?;
+    let n_elements = reader.check_count(line, header[1])?;
+
+    let mut elements = Vec::with_capacity(n_elements);
+    for _ in 0..n_blocks {
+        let (line, fields) = reader.record()?;
+        let block = reader.parse_all::<i32>(line, &fields)?;
+        if block.len() != 4 {
+            let message = "expected 4 numbers in an element block header".to_string();
+            return Err(reader.error(line, message));
+        }
+        let (dimension, entity, element_type) = (block[0], block[1], block[2]);
+        let n_nodes = n_nodes(reader, line, element_type)?;
+        let physical_group = if dimension == 1 {
+            *curve_groups.get(&entity).unwrap_or(&0)
+        } else {
+            0
+        };
+        if block[3] < 0 {
+            return Err(reader.error(line, "expected a non-negative element count".to_string()));
+        }
+        let n = reader.check_count(line, block[3] as usize)?;
+        for _ in 0..n {
+            let (line, fields) = reader.record()?;
+            let numbers = reader.parse_all::<i32>(line, &fields)?;
+            if numbers.len() != 1 + n_nodes {
+                return Err(reader.error(line, format!("expected a tag and {} nodes", n_nodes)));
+            }
+            elements.push(Element {
+                line,
+                element_type,
+                physical_group,
+                nodes: numbers[1..].to_vec(),
+            });
+        }
+    }
+    if elements.len() != n_elements {
+        let message = format!(
+            "{} elements are counted, but the blocks hold {}",
+            n_elements,
+            elements.len()
+        );
+        return Err(reader.error(header_line, message));
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_gmsh;
+    use distmesh::mesh::{Mesh, MeshError};
+    use std::path::Path;
+
+    // The square [0, 1]^2 split along its diagonal, with the bottom edge in physical group 1
+    // and the other three in group 2. The second triangle is listed clockwise.
+    const SQUARE_V2: &str = "$MeshFormat
+2.2 0 8
+$EndMeshFormat
+$PhysicalNames
+3
+1 1 \"bottom\"
+1 2 \"walls\"
+2 3 \"domain\"
+$EndPhysicalNames
+$Nodes
+4
+1 0 0 0
+2 1 0 0
+3 1 1 0
+4 0 1 0
+$EndNodes
+$Elements
+7
+1 15 2 0 1 1
+2 1 2 1 1 1 2
+3 1 2 2 2 2 3
+4 1 2 2 3 3 4
+5 1 2 2 4 4 1
+6 2 2 3 1 1 2 3
+7 2 2 3 1 1 4 3
+$EndElements
+";
+
+    const SQUARE_V4: &str = "$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$Entities
+1 4 1 0
+1 0 0 0 0
+1 0 0 0 1 0 0 1 1 2 1 -2
+2 1 0 0 1 1 0 1 2 2 2 -3
+3 0 1 0 1 1 0 1 2 2 3 -4
+4 0 0 0 0 1 0 1 2 2 4 -1
+1 0 0 0 1 1 0 1 3 4 1 2 3 4
+$EndEntities
+$Nodes
+2 4 1 4
+0 1 0 1
+1
+0 0 0
+2 1 0 3
+2
+3
+4
+1 0 0
+1 1 0
+0 1 0
+$EndNodes
+$Elements
+6 7 1 7
+0 1 15 1
+1 1
+1 1 1 1
+2 1 2
+1 2 1 1
+3 2 3
+1 3 1 1
+4 3 4
+1 4 1 1
+5 4 1
+2 1 2 2
+6 1 2 3
+7 1 4 3
+$EndElements
+";
+
+    fn check_square(mesh: &Mesh) {
+        assert_eq!(mesh.points.len(), 4);
+        assert_eq!(mesh.triangles.len(), 2);
+        assert_eq!(mesh.boundary_edges.len(), 4);
+        assert_eq!(mesh.boundary_tag(0, 1), Some(1));
+        assert_eq!(mesh.boundary_tag(2, 1), Some(2));
+        assert_eq!(mesh.boundary_tag(3, 0), Some(2));
+        assert_eq!(mesh.boundary_tag(0, 2), None);
+
+        for t in mesh.triangles.iter() {
+            let (p, q, r) = (
+                &mesh.points[t.a as usize],
+                &mesh.points[t.b as usize],
+                &mesh.points[t.c as usize],
+            );
+            assert!((q.x - p.x) * (r.y - p.y) - (r.x - p.x) * (q.y - p.y) > 0.);
+        }
+    }
+
+    #[test]
+    fn test_msh_2() {
+        let mesh = parse_gmsh(Path::new("square.msh"), SQUARE_V2).expect("could not read it");
+        check_square(&mesh);
+    }
+
+    #[test]
+    fn test_msh_4() {
+        let mesh = parse_gmsh(Path::new("square.msh"), SQUARE_V4).expect("could not read it");
+        check_square(&mesh);
+    }
+
+    #[test]
+    fn test_refine_keeps_tags() {
+        let mesh = parse_gmsh(Path::new("square.msh"), SQUARE_V2).expect("could not read it");
+        let refined = mesh.refine();
+        assert_eq!(refined.boundary_edges.len(), 8);
+        assert!(refined.boundary_edges.iter().filter(|e| e.tag == 1).count() == 2);
+    }
+
+    #[test]
+    fn test_errors() {
+        let path = Path::new("square.msh");
+        let error_line = |file: &str| match parse_gmsh(path, file) {
+            Err(MeshError::Parse { line, .. }) => line,
+            _ => panic!("expected a parse error"),
+        };
+
+        // A quadrangle, which is not supported
+        let quad = SQUARE_V2.replace("7 2 2 3 1 1 4 3", "7 3 2 3 1 1 2 3 4");
+        assert_eq!(error_line(&quad), 25);
+        // A reference to a node which does not exist
+        let missing = SQUARE_V2.replace("6 2 2 3 1 1 2 3", "6 2 2 3 1 1 2 9");
+        assert_eq!(error_line(&missing), 24);
+        // A truncated file
+        let truncated: String = SQUARE_V4.lines().take(22).collect::<Vec<_>>().join("\n");
+        assert_eq!(error_line(&truncated), 22);
+        // Counts which run past the end of the file
+        let nodes = SQUARE_V2.replace("$Nodes\n4", "$Nodes\n18446744073709551615");
+        assert_eq!(error_line(&nodes), 11);
+        let elements = SQUARE_V2.replace("$Elements\n7", "$Elements\n1000");
+        assert_eq!(error_line(&elements), 18);
+        let tags = SQUARE_V2.replace("6 2 2 3 1 1 2 3", "6 2 -1 3 1 1 2 3");
+        assert_eq!(error_line(&tags), 24);
+        let nodes = SQUARE_V4.replace("2 4 1 4", "2 99999999 1 4");
+        assert_eq!(error_line(&nodes), 14);
+        let block = SQUARE_V4.replace("0 1 0 1\n", "0 1 0 -1\n");
+        assert_eq!(error_line(&block), 15);
+        let blocks = SQUARE_V4.replace("6 7 1 7", "1000 7 1 7");
+        assert_eq!(error_line(&blocks), 27);
+        let block = SQUARE_V4.replace("2 1 2 2\n", "2 1 2 2000\n");
+        assert_eq!(error_line(&block), 38);
+        let block = SQUARE_V4.replace("0 1 15 1\n", "0 1 15 -1\n");
+        assert_eq!(error_line(&block), 28);
+        // Element blocks which do not add up to the count in the header
+        let total = SQUARE_V4.replace("6 7 1 7", "6 6 1 7");
+        assert_eq!(error_line(&total), 27);
+        assert!(parse_gmsh(path, &SQUARE_V2.replace("2.2 0 8", "2.2 1 8")).is_err());
+        assert!(parse_gmsh(path, &SQUARE_V2.replace("2.2 0 8", "3.0 0 8")).is_err());
+    }
+}
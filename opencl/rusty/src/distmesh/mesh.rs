@@ -1,4 +1,8 @@
+use std::collections::HashMap;
+use std::error::Error;
 use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
 
 pub struct Point2D {
     pub x: f64,
@@ -18,7 +22,190 @@ pub struct Triangle {
     pub c: i32,
 }
 
+// The triangle through the points with the given indices, listed counter-clockwise.
+pub fn counter_clockwise(points: &Vec<Point2D>, indices: &[i32]) -> Triangle {
+    let (p, q, r) = (
+        &points[indices[0] as usize],
+        &points[indices[1] as usize],
+        &points[indices[2] as usize],
+    );
+    let signed_area = (q.x - p.x) * (r.y - p.y) - (r.x - p.x) * (q.y - p.y);
+    if signed_area < 0. {
+        Triangle {
+            a: indices[0],
+            b: indices[2],
+            c: indices[1],
+        }
+    } else {
+        Triangle {
+            a: indices[0],
+            b: indices[1],
+            c: indices[2],
+        }
+    }
+}
+
+// An edge on the boundary of the mesh, tagged with the part of the boundary it belongs to,
+// e.g. the physical group of a Gmsh line element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundaryEdge {
+    // Indices referring to the points of the mesh
+    pub a: i32,
+    pub b: i32,
+    pub tag: i32,
+}
+
 pub struct Mesh {
     pub points: Vec<Point2D>,
     pub triangles: Vec<Triangle>,
+    // Empty unless the mesh was read from a format which tags its boundaries.
+    pub boundary_edges: Vec<BoundaryEdge>,
+}
+
+// An error reading a mesh from a file.
+#[derive(Debug)]
+pub enum MeshError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    // A malformed line, numbered from 1.
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl MeshError {
+    pub fn io(path: &Path, error: io::Error) -> MeshError {
+        MeshError::Io {
+            path: path.to_path_buf(),
+            error,
+        }
+    }
+
+    pub fn parse(path: &Path, line: usize, message: String) -> MeshError {
+        MeshError::Parse {
+            path: path.to_path_buf(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            MeshError::Io {
+                ref path,
+                ref error,
+            } => write!(f, "{}: {}", path.display(), error),
+            MeshError::Parse {
+                ref path,
+                line,
+                ref message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl Error for MeshError {}
+
+impl Mesh {
+    // Split every triangle into four by joining the midpoints of its edges. The children keep
+    // the orientation of their parent, and the midpoint of an edge shared by two triangles is
+    // only added once. Tagged boundary edges are split in two, keeping their tags.
+    pub fn refine(&self) -> Mesh {
+        let mut points: Vec<Point2D> = self
+            .points
+            .iter()
+            .map(|p| Point2D { x: p.x, y: p.y })
+            .collect();
+        let mut midpoints: HashMap<(i32, i32), i32> = HashMap::new();
+        let mut midpoint = |a: i32, b: i32, points: &mut Vec<Point2D>| {
+            let key = if a < b { (a, b) } else { (b, a) };
+            *midpoints.entry(key).or_insert_with(|| {
+                let (p, q) = (&points[a as usize], &points[b as usize]);
+                let m = Point2D {
+                    x: (p.x + q.x) / 2.,
+                    y: (p.y + q.y) / 2.,
+                };
+                points.push(m);
+                points.len() as i32 - 1
+            })
+        };
+
+        let mut triangles = vec![];
+        for t in self.triangles.iter() {
+            let ab = midpoint(t.a, t.b, &mut points);
+            let bc = midpoint(t.b, t.c, &mut points);
+            let ca = midpoint(t.c, t.a, &mut points);
+            triangles.push(Triangle { a: t.a, b: ab, c: ca });
+            triangles.push(Triangle { a: ab, b: t.b, c: bc });
+            triangles.push(Triangle { a: ca, b: bc, c: t.c });
+            triangles.push(Triangle { a: ab, b: bc, c: ca });
+        }
+
+        let mut boundary_edges = vec![];
+        for e in self.boundary_edges.iter() {
+            let m = midpoint(e.a, e.b, &mut points);
+            boundary_edges.push(BoundaryEdge { b: m, ..*e });
+            boundary_edges.push(BoundaryEdge { a: m, ..*e });
+        }
+
+        Mesh {
+            points,
+            triangles,
+            boundary_edges,
+        }
+    }
+
+    // The tag of the boundary edge between points a and b, in either direction.
+    pub fn boundary_tag(&self, a: i32, b: i32) -> Option<i32> {
+        self.boundary_edges
+            .iter()
+            .find(|e| (e.a, e.b) == (a, b) || (e.a, e.b) == (b, a))
+            .map(|e| e.tag)
+    }
+
+    // The length of the longest edge in the mesh, the usual measure of its size h.
+    pub fn max_edge_length(&self) -> f64 {
+        let length = |a: i32, b: i32| {
+            let (p, q) = (&self.points[a as usize], &self.points[b as usize]);
+            ((p.x - q.x).powi(2) + (p.y - q.y).powi(2)).sqrt()
+        };
+        self.triangles
+            .iter()
+            .map(|t| length(t.a, t.b).max(length(t.b, t.c)).max(length(t.c, t.a)))
+            .fold(0., f64::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoundaryEdge, Mesh, Point2D, Triangle};
+
+    #[test]
+    fn test_refine() {
+        let mesh = Mesh {
+            points: vec![
+                Point2D { x: 0., y: 0. },
+                Point2D { x: 1., y: 0. },
+                Point2D { x: 1., y: 1. },
+                Point2D { x: 0., y: 1. },
+            ],
+            triangles: vec![Triangle { a: 0, b: 1, c: 2 }, Triangle { a: 0, b: 2, c: 3 }],
+            boundary_edges: vec![BoundaryEdge { a: 0, b: 1, tag: 7 }],
+        };
+        let refined = mesh.refine();
+        assert_eq!(refined.triangles.len(), 8);
+        // The diagonal's midpoint is shared by both triangles.
+        assert_eq!(refined.points.len(), 9);
+        // The bottom edge's midpoint is the first one added.
+        assert_eq!(refined.boundary_tag(0, 4), Some(7));
+        assert_eq!(refined.boundary_tag(1, 4), Some(7));
+        assert_eq!(refined.boundary_tag(0, 1), None);
+        assert!((refined.max_edge_length() - mesh.max_edge_length() / 2.).abs() < 1e-12);
+    }
 }
@@ -1,5 +1,14 @@
 /**
-Module for code to parse the output of the distmesh algorithm.
+Module for generating 2D triangular meshes, with the DistMesh algorithm or on structured
+grids, and for reading and writing them in the formats of the Matlab DistMesh program, Gmsh
+and Triangle.
  */
+pub mod delaunay;
+pub mod distance;
 pub mod distmesh_2d;
+pub mod generator;
+pub mod gmsh;
 pub mod mesh;
+mod reader;
+pub mod structured;
+pub mod triangle;
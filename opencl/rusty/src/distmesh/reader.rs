@@ -0,0 +1,86 @@
+use distmesh::mesh::MeshError;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+// Reads the records of a mesh file one at a time, as split into whitespace-separated fields
+// by `distmesh_2d::records`, and makes errors which point at the line they came from.
+pub struct Reader<'a> {
+    path: &'a Path,
+    records: Vec<(usize, Vec<&'a str>)>,
+    // The index of the next record to read
+    next: usize,
+    // The number of the last line read
+    pub line: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(path: &'a Path, records: Vec<(usize, Vec<&'a str>)>) -> Reader<'a> {
+        Reader {
+            path,
+            records,
+            next: 0,
+            line: 0,
+        }
+    }
+
+    pub fn next_record(&mut self) -> Option<(usize, Vec<&'a str>)> {
+        let record = self.records.get(self.next).cloned();
+        if let Some((line, _)) = record {
+            self.next += 1;
+            self.line = line;
+        }
+        record
+    }
+
+    // The next record, which must exist.
+    pub fn record(&mut self) -> Result<(usize, Vec<&'a str>), MeshError> {
+        match self.next_record() {
+            Some(record) => Ok(record),
+            None => Err(self.error(self.line, "unexpected end of file".to_string())),
+        }
+    }
+
+    // A record holding a single count of the records which follow it.
+    pub fn count(&mut self) -> Result<usize, MeshError> {
+        let (line, fields) = self.record()?;
+        if fields.len() != 1 {
+            return Err(self.error(line, "expected a count".to_string()));
+        }
+        let n = self.parse::<usize>(line, fields[0])?;
+        self.check_count(line, n)
+    }
+
+    // Check that the `n` records counted on line `line` can still be there, so that a
+    // corrupt count is reported before anything is allocated for it.
+    pub fn check_count(&self, line: usize, n: usize) -> Result<usize, MeshError> {
+        let remaining = self.records.len() - self.next;
+        if n > remaining {
+            let message = format!("{} records are counted, but only {} are left", n, remaining);
+            return Err(self.error(line, message));
+        }
+        Ok(n)
+    }
+
+    pub fn parse<T>(&self, line: usize, field: &str) -> Result<T, MeshError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        field
+            .parse::<T>()
+            .map_err(|e| self.error(line, format!("could not parse {:?}: {}", field, e)))
+    }
+
+    pub fn parse_all<T>(&self, line: usize, fields: &[&str]) -> Result<Vec<T>, MeshError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        fields.iter().map(|field| self.parse(line, field)).collect()
+    }
+
+    pub fn error(&self, line: usize, message: String) -> MeshError {
+        MeshError::parse(self.path, line, message)
+    }
+}
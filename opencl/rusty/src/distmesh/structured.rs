@@ -0,0 +1,354 @@
+use distmesh::mesh::{BoundaryEdge, Mesh, Point2D, Triangle};
+use std::f64::consts;
+
+// The tags of the sides of `Mesh::rectangle`.
+pub const BOTTOM: i32 = 1;
+pub const RIGHT: i32 = 2;
+pub const TOP: i32 = 3;
+pub const LEFT: i32 = 4;
+
+// The tags of the circles bounding `Mesh::annulus` and `Mesh::disk`.
+pub const INNER: i32 = 1;
+pub const OUTER: i32 = 2;
+
+// How the cells of a structured mesh are split into triangles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Diagonals {
+    // Along the diagonal from the lower left to the upper right corner of each cell.
+    Right,
+    // Along the diagonal from the lower right to the upper left corner.
+    Left,
+    // Right and Left in a checkerboard pattern, so that each interior vertex is shared by
+    // either four or eight triangles.
+    Alternating,
+    // Along both diagonals, into four triangles meeting at the center of the cell.
+    Crossed,
+}
+
+impl Mesh {
+    // The rectangle [x0, x1] × [y0, y1], divided into nx by ny equal cells. The grid points are
+    // numbered row by row from the lower left corner, and the centers of crossed cells follow
+    // them. The sides are tagged BOTTOM, RIGHT, TOP and LEFT.
+    pub fn rectangle(
+        x0: f64,
+        x1: f64,
+        y0: f64,
+        y1: f64,
+        nx: usize,
+        ny: usize,
+        diagonals: Diagonals,
+    ) -> Mesh {
+        assert!(nx > 0 && ny > 0, "the rectangle needs at least one cell");
+        assert!(x0 < x1 && y0 < y1, "the rectangle is empty");
+        let x = |i: usize| x0 + (x1 - x0) * i as f64 / nx as f64;
+        let y = |j: usize| y0 + (y1 - y0) * j as f64 / ny as f64;
+        let index = |i: usize, j: usize| (j * (nx + 1) + i) as i32;
+
+        let mut points = vec![];
+        for j in 0..ny + 1 {
+            for i in 0..nx + 1 {
+                points.push(Point2D { x: x(i), y: y(j) });
+            }
+        }
+        let mut triangles = vec![];
+        for j in 0..ny {
+            for i in 0..nx {
+                let corners = [
+                    index(i, j),
+                    index(i + 1, j),
+                    index(i + 1, j + 1),
+                    index(i, j + 1),
+                ];
+                let center = Point2D {
+                    x: (x(i) + x(i + 1)) / 2.,
+                    y: (y(j) + y(j + 1)) / 2.,
+                };
+                split_cell(
+                    &mut points,
+                    &mut triangles,
+                    diagonals,
+                    (i + j) % 2 == 1,
+                    corners,
+                    center,
+                );
+            }
+        }
+
+        // Counter-clockwise around the rectangle.
+        let mut boundary_edges = vec![];
+        for i in 0..nx {
+            boundary_edges.push(edge(index(i, 0), index(i + 1, 0), BOTTOM));
+        }
+        for j in 0..ny {
+            boundary_edges.push(edge(index(nx, j), index(nx, j + 1), RIGHT));
+        }
+        for i in (0..nx).rev() {
+            boundary_edges.push(edge(index(i + 1, ny), index(i, ny), TOP));
+        }
+        for j in (0..ny).rev() {
+            boundary_edges.push(edge(index(0, j + 1), index(0, j), LEFT));
+        }
+
+        Mesh {
+            points,
+            triangles,
+            boundary_edges,
+        }
+    }
+
+    // The annulus about the origin between radii r_inner and r_outer, divided into n_radial
+    // rings of n_angular cells. The cells are split as in `rectangle`, with the radius taking
+    // the place of x and the angle that of y, and the circles are tagged INNER and OUTER. The
+    // circles are polygons of n_angular sides, so the area is a little less than that of the
+    // true annulus.
+    pub fn annulus(
+        r_inner: f64,
+        r_outer: f64,
+        n_radial: usize,
+        n_angular: usize,
+        diagonals: Diagonals,
+    ) -> Mesh {
+        assert!(
+            0. < r_inner && r_inner < r_outer,
+            "the radii must be 0 < r_inner < r_outer"
+        );
+        assert!(n_radial > 0, "there must be at least one ring");
+        let mut mesh = Mesh {
+            points: vec![],
+            triangles: vec![],
+            boundary_edges: vec![],
+        };
+        add_rings(&mut mesh, r_inner, r_outer, n_radial, n_angular, diagonals);
+        for m in 0..n_angular {
+            let (a, b) = (m as i32, ((m + 1) % n_angular) as i32);
+            // Clockwise, which keeps the mesh on the left of the edge.
+            mesh.boundary_edges.push(edge(b, a, INNER));
+        }
+        mesh
+    }
+
+    // The disk of radius r about the origin, divided into n_radial rings of n_angular cells.
+    // The innermost ring is a fan of triangles about the center, which is the last point, and
+    // the others are split as in `annulus`. The circle is tagged OUTER.
+    pub fn disk(r: f64, n_radial: usize, n_angular: usize, diagonals: Diagonals) -> Mesh {
+        assert!(r > 0., "the radius must be positive");
+        assert!(n_radial > 0, "there must be at least one ring");
+        let mut mesh = Mesh {
+            points: vec![],
+            triangles: vec![],
+            boundary_edges: vec![],
+        };
+        let r_inner = r / n_radial as f64;
+        add_rings(&mut mesh, r_inner, r, n_radial - 1, n_angular, diagonals);
+
+        let center = mesh.points.len() as i32;
+        mesh.points.push(Point2D { x: 0., y: 0. });
+        for m in 0..n_angular {
+            mesh.triangles.push(Triangle {
+                a: center,
+                b: m as i32,
+                c: ((m + 1) % n_angular) as i32,
+            });
+        }
+        mesh
+    }
+}
+
+// Add the points and triangles of n_radial rings between radii r_inner and r_outer to an empty
+// mesh, numbering the points ring by ring from the inside, and tag the outer circle OUTER.
+// With no rings, there is only the circle of radius r_inner = r_outer.
+fn add_rings(
+    mesh: &mut Mesh,
+    r_inner: f64,
+    r_outer: f64,
+    n_radial: usize,
+    n_angular: usize,
+    diagonals: Diagonals,
+) {
+    assert!(n_angular >= 3, "the circles need at least three sides");
+    let dr = (r_outer - r_inner) / n_radial.max(1) as f64;
+    let r = |k: f64| r_inner + dr * k;
+    let theta = |m: f64| 2. * consts::PI * m / n_angular as f64;
+    let polar = |k: f64, m: f64| Point2D {
+        x: r(k) * theta(m).cos(),
+        y: r(k) * theta(m).sin(),
+    };
+    let index = |k: usize, m: usize| (k * n_angular + m % n_angular) as i32;
+
+    for k in 0..n_radial + 1 {
+        for m in 0..n_angular {
+            mesh.points.push(polar(k as f64, m as f64));
+        }
+    }
+    for k in 0..n_radial {
+        for m in 0..n_angular {
+            let corners = [
+                index(k, m),
+                index(k + 1, m),
+                index(k + 1, m + 1),
+                index(k, m + 1),
+            ];
+            let center = polar(k as f64 + 0.5, m as f64 + 0.5);
+            let odd = (k + m) % 2 == 1;
+            split_cell(
+                &mut mesh.points,
+                &mut mesh.triangles,
+                diagonals,
+                odd,
+                corners,
+                center,
+            );
+        }
+    }
+    for m in 0..n_angular {
+        mesh.boundary_edges
+            .push(edge(index(n_radial, m), index(n_radial, m + 1), OUTER));
+    }
+}
+
+// Split the cell with the given corners, counter-clockwise from the lower left, into
+// triangles. `odd` picks the diagonal of an alternating cell, and `center` is added to the
+// points if the cell is crossed.
+fn split_cell(
+    points: &mut Vec<Point2D>,
+    triangles: &mut Vec<Triangle>,
+    diagonals: Diagonals,
+    odd: bool,
+    corners: [i32; 4],
+    center: Point2D,
+) {
+    let [sw, se, ne, nw] = corners;
+    let triangle = |a, b, c| Triangle { a, b, c };
+    match diagonals {
+        Diagonals::Right => {
+            triangles.push(triangle(sw, se, ne));
+            triangles.push(triangle(sw, ne, nw));
+        }
+        Diagonals::Left => {
+            triangles.push(triangle(sw, se, nw));
+            triangles.push(triangle(se, ne, nw));
+        }
+        Diagonals::Alternating => {
+            let diagonals = if odd {
+                Diagonals::Left
+            } else {
+                Diagonals::Right
+            };
+            split_cell(points, triangles, diagonals, odd, corners, center);
+        }
+        Diagonals::Crossed => {
+            let c = points.len() as i32;
+            points.push(center);
+            triangles.push(triangle(sw, se, c));
+            triangles.push(triangle(se, ne, c));
+            triangles.push(triangle(ne, nw, c));
+            triangles.push(triangle(nw, sw, c));
+        }
+    }
+}
+
+fn edge(a: i32, b: i32, tag: i32) -> BoundaryEdge {
+    BoundaryEdge { a, b, tag }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagonals, BOTTOM, INNER, LEFT, OUTER, RIGHT, TOP};
+    use distmesh::mesh::Mesh;
+    use std::f64::consts;
+
+    // The area of the mesh, checking that its triangles are counter-clockwise and that each
+    // boundary edge is an edge of a triangle in the same direction, so that the mesh is on its
+    // left.
+    fn area(mesh: &Mesh) -> f64 {
+        let mut area = 0.;
+        for t in mesh.triangles.iter() {
+            let (a, b, c) = (
+                &mesh.points[t.a as usize],
+                &mesh.points[t.b as usize],
+                &mesh.points[t.c as usize],
+            );
+            let signed_area = ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)) / 2.;
+            assert!(signed_area > 0., "the triangles must be counter-clockwise");
+            area += signed_area;
+        }
+        for e in mesh.boundary_edges.iter() {
+            assert!(mesh
+                .triangles
+                .iter()
+                .any(|t| { [(t.a, t.b), (t.b, t.c), (t.c, t.a)].contains(&(e.a, e.b)) }));
+        }
+        area
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let patterns = [
+            (Diagonals::Right, 24, 20),
+            (Diagonals::Left, 24, 20),
+            (Diagonals::Alternating, 24, 20),
+            (Diagonals::Crossed, 48, 32),
+        ];
+        for &(diagonals, n_triangles, n_points) in patterns.iter() {
+            let mesh = Mesh::rectangle(-1., 2., 0., 0.5, 4, 3, diagonals);
+            assert_eq!(mesh.triangles.len(), n_triangles);
+            assert_eq!(mesh.points.len(), n_points);
+            assert!((area(&mesh) - 1.5).abs() < 1e-12);
+
+            let tags: Vec<i32> = mesh.boundary_edges.iter().map(|e| e.tag).collect();
+            assert_eq!(tags.len(), 14);
+            assert_eq!(tags.iter().filter(|&&tag| tag == BOTTOM).count(), 4);
+            assert_eq!(tags.iter().filter(|&&tag| tag == RIGHT).count(), 3);
+            assert_eq!(tags.iter().filter(|&&tag| tag == TOP).count(), 4);
+            assert_eq!(tags.iter().filter(|&&tag| tag == LEFT).count(), 3);
+        }
+
+        // The lower left cell is split along the diagonal from point 0 to point 6.
+        let right = Mesh::rectangle(0., 1., 0., 1., 4, 3, Diagonals::Right);
+        assert!(right.triangles.iter().any(|t| (t.a, t.b, t.c) == (0, 1, 6)));
+        let alternating = Mesh::rectangle(0., 1., 0., 1., 4, 3, Diagonals::Alternating);
+        assert!(alternating
+            .triangles
+            .iter()
+            .any(|t| (t.a, t.b, t.c) == (1, 2, 6)));
+    }
+
+    #[test]
+    fn test_annulus_and_disk() {
+        // The circles are regular polygons.
+        let polygon = |r: f64, n: usize| n as f64 / 2. * (2. * consts::PI / n as f64).sin() * r * r;
+
+        for &diagonals in [Diagonals::Right, Diagonals::Alternating, Diagonals::Crossed].iter() {
+            let annulus = Mesh::annulus(0.5, 2., 3, 16, diagonals);
+            let exact = polygon(2., 16) - polygon(0.5, 16);
+            assert!((area(&annulus) - exact).abs() < 1e-12 * exact);
+            assert_eq!(
+                annulus
+                    .boundary_edges
+                    .iter()
+                    .filter(|e| e.tag == INNER)
+                    .count(),
+                16
+            );
+            assert_eq!(
+                annulus
+                    .boundary_edges
+                    .iter()
+                    .filter(|e| e.tag == OUTER)
+                    .count(),
+                16
+            );
+        }
+
+        let fan = Mesh::disk(1., 1, 6, Diagonals::Right);
+        assert_eq!(fan.triangles.len(), 6);
+        assert!((area(&fan) - polygon(1., 6)).abs() < 1e-12);
+
+        let disk = Mesh::disk(1., 4, 12, Diagonals::Left);
+        assert_eq!(disk.triangles.len(), 12 + 2 * 3 * 12);
+        assert_eq!(disk.points.len(), 4 * 12 + 1);
+        assert!((area(&disk) - polygon(1., 12)).abs() < 1e-12);
+        assert_eq!(disk.boundary_edges.len(), 12);
+        assert!(disk.boundary_edges.iter().all(|e| e.tag == OUTER));
+    }
+}
@@ -0,0 +1,424 @@
+use distmesh::mesh::{counter_clockwise, BoundaryEdge, Mesh, MeshError, Point2D};
+use distmesh::reader::Reader;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+// Read a mesh made by Shewchuk's Triangle from its .node and .ele files, and optionally the
+// .poly file holding its boundary segments. The vertices may be numbered from 0 or from 1,
+// which is decided by the first vertex in the .node file, and the other files must follow
+// it. Quadratic triangles are read as their three corners, and attributes are ignored.
+//
+// The boundary edges are the segments of the .poly file, tagged with their markers. Without
+// one, an edge of the boundary of the triangulation is tagged only if both of its ends carry
+// the same nonzero marker in the .node file.
+pub fn load_triangle(
+    node_path: &Path,
+    ele_path: &Path,
+    poly_path: Option<&Path>,
+) -> Result<Mesh, MeshError> {
+    let node_file = read(node_path)?;
+    let nodes = read_nodes(&mut Reader::new(node_path, triangle_records(&node_file)))?;
+    let first_index = nodes.first_index.unwrap_or(0);
+    let n_points = nodes.points.len();
+
+    let ele_file = read(ele_path)?;
+    let mut reader = Reader::new(ele_path, triangle_records(&ele_file));
+    let triangles = read_elements(&mut reader, first_index, n_points)?
+        .iter()
+        .map(|corners| counter_clockwise(&nodes.points, corners))
+        .collect();
+
+    let mut mesh = Mesh {
+        points: nodes.points,
+        triangles,
+        boundary_edges: vec![],
+    };
+    mesh.boundary_edges = match poly_path {
+        Some(poly_path) => {
+            let poly_file = read(poly_path)?;
+            let mut reader = Reader::new(poly_path, triangle_records(&poly_file));
+            read_poly(&mut reader, first_index, n_points)?
+        }
+        None => marked_boundary(&mesh, &nodes.markers),
+    };
+    Ok(mesh)
+}
+
+// Write `mesh` as the Triangle files `stem.node`, `stem.ele` and `stem.poly`, numbered from
+// 1 as Triangle numbers its own output. The boundary edges become the segments of the .poly
+// file, and each vertex is marked with the tag of a boundary edge it lies on, or 0.
+pub fn save_triangle(mesh: &Mesh, stem: &Path) -> io::Result<()> {
+    let mut markers = vec![0; mesh.points.len()];
+    for e in mesh.boundary_edges.iter() {
+        markers[e.a as usize] = e.tag;
+        markers[e.b as usize] = e.tag;
+    }
+
+    let mut node = BufWriter::new(File::create(stem.with_extension("node"))?);
+    writeln!(node, "{} 2 0 1", mesh.points.len())?;
+    for (i, (p, marker)) in mesh.points.iter().zip(markers.iter()).enumerate() {
+        writeln!(node, "{} {} {} {}", i + 1, p.x, p.y, marker)?;
+    }
+    node.flush()?;
+
+    let mut ele = BufWriter::new(File::create(stem.with_extension("ele"))?);
+    writeln!(ele, "{} 3 0", mesh.triangles.len())?;
+    for (i, t) in mesh.triangles.iter().enumerate() {
+        writeln!(ele, "{} {} {} {}", i + 1, t.a + 1, t.b + 1, t.c + 1)?;
+    }
+    ele.flush()?;
+
+    let mut poly = BufWriter::new(File::create(stem.with_extension("poly"))?);
+    // The vertices are in the .node file.
+    writeln!(poly, "0 2 0 1")?;
+    writeln!(poly, "{} 1", mesh.boundary_edges.len())?;
+    for (i, e) in mesh.boundary_edges.iter().enumerate() {
+        writeln!(poly, "{} {} {} {}", i + 1, e.a + 1, e.b + 1, e.tag)?;
+    }
+    writeln!(poly, "0")?;
+    poly.flush()
+}
+
+fn read(path: &Path) -> Result<String, MeshError> {
+    fs::read_to_string(path).map_err(|e| MeshError::io(path, e))
+}
+
+// The line number and the whitespace-separated fields of each line of `file` which is not
+// blank, where # starts a comment which runs to the end of the line.
+fn triangle_records(file: &str) -> Vec<(usize, Vec<&str>)> {
+    file.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let text = line.split('#').next().unwrap_or("");
+            (i + 1, text.split_whitespace().collect::<Vec<&str>>())
+        })
+        .filter(|&(_, ref fields)| !fields.is_empty())
+        .collect()
+}
+
+struct Nodes {
+    points: Vec<Point2D>,
+    markers: Vec<i32>,
+    // The number of the first vertex, if there are any.
+    first_index: Option<i32>,
+}
+
+// The vertices of a .node file, or those at the top of a .poly file.
+fn read_nodes(reader: &mut Reader) -> Result<Nodes, MeshError> {
+    let (line, fields) = reader.record()?;
+    let header = reader.parse_all::<usize>(line, &fields)?;
+    if header.len() != 4 {
+        let message = "expected vertices, dimension, attributes and markers".to_string();
+        return Err(reader.error(line, message));
+    }
+    let (n, dimension, n_attributes, n_markers) = (header[0], header[1], header[2], header[3]);
+    if dimension != 2 {
+        return Err(reader.error(line, format!("expected dimension 2, found {}", dimension)));
+    }
+    if n_markers > 1 {
+        return Err(reader.error(line, "expected at most one boundary marker".to_string()));
+    }
+    let n = reader.check_count(line, n)?;
+    let n_fields = field_count(reader, line, 3 + n_markers, n_attributes)?;
+
+    let mut nodes = Nodes {
+        points: Vec::with_capacity(n),
+        markers: Vec::with_capacity(n),
+        first_index: None,
+    };
+    for i in 0..n {
+        let (line, fields) = reader.record()?;
+        if fields.len() != n_fields {
+            return Err(reader.error(line, format!("expected {} fields", n_fields)));
+        }
+        let number = reader.parse::<i32>(line, fields[0])?;
+        let first_index = *nodes.first_index.get_or_insert(number);
+        if i == 0 && number != 0 && number != 1 {
+            let message = "vertices must be numbered from 0 or 1".to_string();
+            return Err(reader.error(line, message));
+        }
+        if number != first_index + i as i32 {
+            let message = format!("expected vertex {}", first_index + i as i32);
+            return Err(reader.error(line, message));
+        }
+        let x = reader.parse::<f64>(line, fields[1])?;
+        let y = reader.parse::<f64>(line, fields[2])?;
+        nodes.points.push(Point2D { x, y });
+        let marker = match n_markers {
+            0 => 0,
+            _ => reader.parse::<i32>(line, fields[3 + n_attributes])?,
+        };
+        nodes.markers.push(marker);
+    }
+    Ok(nodes)
+}
+
+// The corners of each triangle in a .ele file, as 0-based indices.
+fn read_elements(
+    reader: &mut Reader,
+    first_index: i32,
+    n_points: usize,
+) -> Result<Vec<Vec<i32>>, MeshError> {
+    let (line, fields) = reader.record()?;
+    let header = reader.parse_all::<usize>(line, &fields)?;
+    if header.len() != 3 {
+        let message = "expected triangles, nodes per triangle and attributes".to_string();
+        return Err(reader.error(line, message));
+    }
+    let (n, n_nodes, n_attributes) = (header[0], header[1], header[2]);
+    if n_nodes != 3 && n_nodes != 6 {
+        let message = format!("expected 3 or 6 nodes per triangle, found {}", n_nodes);
+        return Err(reader.error(line, message));
+    }
+    let n = reader.check_count(line, n)?;
+    let n_fields = field_count(reader, line, 1 + n_nodes, n_attributes)?;
+
+    let mut elements = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (line, fields) = reader.record()?;
+        if fields.len() != n_fields {
+            return Err(reader.error(line, format!("expected {} fields", n_fields)));
+        }
+        // The corners come first, before the midpoints of the edges of quadratic triangles.
+        let corners = reader.parse_all::<i32>(line, &fields[1..4])?;
+        elements.push(vertices(reader, line, &corners, first_index, n_points)?);
+    }
+    Ok(elements)
+}
+
+// The segments of a .poly file, tagged with their boundary markers, or 0 if it has none.
+fn read_poly(
+    reader: &mut Reader,
+    first_index: i32,
+    n_points: usize,
+) -> Result<Vec<BoundaryEdge>, MeshError> {
+    // Any vertices listed here were the input to Triangle, and are repeated in the .node file.
+    read_nodes(reader)?;
+
+    let (line, fields) = reader.record()?;
+    let header = reader.parse_all::<usize>(line, &fields)?;
+    if header.len() != 2 || header[1] > 1 {
+        let message = "expected segments and at most one boundary marker".to_string();
+        return Err(reader.error(line, message));
+    }
+    let (n, n_markers) = (reader.check_count(line, header[0])?, header[1]);
+
+    let mut edges = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (line, fields) = reader.record()?;
+        if fields.len() != 3 + n_markers {
+            return Err(reader.error(line, format!("expected {} fields", 3 + n_markers)));
+        }
+        let numbers = reader.parse_all::<i32>(line, &fields)?;
+        let ends = vertices(reader, line, &numbers[1..3], first_index, n_points)?;
+        edges.push(BoundaryEdge {
+            a: ends[0],
+            b: ends[1],
+            tag: if n_markers == 1 { numbers[3] } else { 0 },
+        });
+    }
+    // The holes and regional attributes which follow only matter to Triangle.
+    Ok(edges)
+}
+
+// The number of fields on each line of a section, given on line `line` as the `n_fixed`
+// fields every line has and the `n_attributes` which follow, so that a corrupt header cannot
+// overflow it.
+fn field_count(
+    reader: &Reader,
+    line: usize,
+    n_fixed: usize,
+    n_attributes: usize,
+) -> Result<usize, MeshError> {
+    n_attributes
+        .checked_add(n_fixed)
+        .ok_or_else(|| reader.error(line, format!("too many attributes: {}", n_attributes)))
+}
+
+// Convert vertex numbers to 0-based indices.
+fn vertices(
+    reader: &Reader,
+    line: usize,
+    numbers: &[i32],
+    first_index: i32,
+    n_points: usize,
+) -> Result<Vec<i32>, MeshError> {
+    numbers
+        .iter()
+        .map(|&number| {
+            let i = number - first_index;
+            if i < 0 || i as usize >= n_points {
+                Err(reader.error(line, format!("there is no vertex {}", number)))
+            } else {
+                Ok(i)
+            }
+        })
+        .collect()
+}
+
+// The edges of the boundary of the triangulation whose ends carry the same nonzero marker.
+fn marked_boundary(mesh: &Mesh, markers: &Vec<i32>) -> Vec<BoundaryEdge> {
+    // The number of triangles each edge belongs to, and its direction in the first of them.
+    let mut edges: HashMap<(i32, i32), (usize, (i32, i32))> = HashMap::new();
+    for t in mesh.triangles.iter() {
+        for &(a, b) in [(t.a, t.b), (t.b, t.c), (t.c, t.a)].iter() {
+            let key = if a < b { (a, b) } else { (b, a) };
+            edges.entry(key).or_insert((0, (a, b))).0 += 1;
+        }
+    }
+
+    let mut boundary: Vec<BoundaryEdge> = edges
+        .values()
+        .filter(|&&(count, _)| count == 1)
+        .map(|&(_, (a, b))| (a, b, markers[a as usize]))
+        .filter(|&(_, b, tag)| tag != 0 && markers[b as usize] == tag)
+        .map(|(a, b, tag)| BoundaryEdge { a, b, tag })
+        .collect();
+    boundary.sort_by_key(|e| (e.a, e.b));
+    boundary
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use super::{load_triangle, save_triangle};
+    use distmesh::gmsh::parse_gmsh;
+    use distmesh::mesh::MeshError;
+    use std::fs;
+    use std::path::Path;
+
+    // The unit square split along its diagonal, numbered from 0, with the bottom edge marked
+    // 1 and the others 2.
+    const NODE: &str = "# the unit square
+4 2 0 1
+0 0 0 1
+1 1 0 2  # a corner on two sides
+2 1 1 2
+3 0 1 2
+";
+
+    const ELE: &str = "2 3 0
+0 0 1 2
+1 0 2 3
+";
+
+    const POLY: &str = "0 2 0 1
+4 1
+0 0 1 1
+1 1 2 2
+2 2 3 2
+3 3 0 2
+1
+0 0.5 -10
+";
+
+    fn write_files(dir: &Path, node: &str, ele: &str, poly: &str) {
+        fs::write(dir.join("square.node"), node).expect("could not write the .node file");
+        fs::write(dir.join("square.ele"), ele).expect("could not write the .ele file");
+        fs::write(dir.join("square.poly"), poly).expect("could not write the .poly file");
+    }
+
+    #[test]
+    fn test_load_with_segments() {
+        let dir = tempdir().expect("could not create a temporary directory");
+        write_files(dir.path(), NODE, ELE, POLY);
+        let mesh = load_triangle(
+            &dir.path().join("square.node"),
+            &dir.path().join("square.ele"),
+            Some(&dir.path().join("square.poly")),
+        )
+        .expect("could not load the mesh");
+
+        assert_eq!(mesh.points.len(), 4);
+        assert_eq!((mesh.triangles[1].a, mesh.triangles[1].b, mesh.triangles[1].c), (0, 2, 3));
+        assert_eq!(mesh.boundary_tag(0, 1), Some(1));
+        assert_eq!(mesh.boundary_tag(1, 2), Some(2));
+        assert_eq!(mesh.boundary_tag(0, 2), None);
+    }
+
+    #[test]
+    fn test_one_based_without_segments() {
+        let dir = tempdir().expect("could not create a temporary directory");
+        let node = "4 2 0 1\n1 0 0 1\n2 1 0 1\n3 1 1 2\n4 0 1 2\n";
+        let ele = "2 3 0\n1 1 2 3\n2 1 3 4\n";
+        write_files(dir.path(), node, ele, "");
+        let mesh = load_triangle(
+            &dir.path().join("square.node"),
+            &dir.path().join("square.ele"),
+            None,
+        )
+        .expect("could not load the mesh");
+
+        assert_eq!((mesh.triangles[0].a, mesh.triangles[0].b, mesh.triangles[0].c), (0, 1, 2));
+        // The right and left sides join vertices with different markers, so are untagged.
+        assert_eq!(mesh.boundary_edges.len(), 2);
+        assert_eq!(mesh.boundary_tag(0, 1), Some(1));
+        assert_eq!(mesh.boundary_tag(2, 3), Some(2));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let gmsh = "$MeshFormat\n2.2 0 8\n$EndMeshFormat\n$Nodes\n4\n1 0 0 0\n2 1 0 0\n\
+                    3 1 1 0\n4 0 1 0\n$EndNodes\n$Elements\n4\n1 1 2 5 1 1 2\n\
+                    2 1 2 6 2 3 4\n3 2 2 1 1 1 2 3\n4 2 2 1 1 1 3 4\n$EndElements\n";
+        let mesh = parse_gmsh(Path::new("square.msh"), gmsh)
+            .expect("could not read the mesh")
+            .refine();
+
+        let dir = tempdir().expect("could not create a temporary directory");
+        let stem = dir.path().join("square");
+        save_triangle(&mesh, &stem).expect("could not write the mesh");
+        let read = load_triangle(
+            &stem.with_extension("node"),
+            &stem.with_extension("ele"),
+            Some(&stem.with_extension("poly")),
+        )
+        .expect("could not read the mesh back");
+
+        assert_eq!(read.points.len(), mesh.points.len());
+        for (p, q) in read.points.iter().zip(mesh.points.iter()) {
+            assert_eq!((p.x, p.y), (q.x, q.y));
+        }
+        for (s, t) in read.triangles.iter().zip(mesh.triangles.iter()) {
+            assert_eq!((s.a, s.b, s.c), (t.a, t.b, t.c));
+        }
+        assert_eq!(read.boundary_edges, mesh.boundary_edges);
+    }
+
+    #[test]
+    fn test_errors() {
+        let dir = tempdir().expect("could not create a temporary directory");
+        let error_line = |node: &str, ele: &str, poly: &str| {
+            write_files(dir.path(), node, ele, poly);
+            let result = load_triangle(
+                &dir.path().join("square.node"),
+                &dir.path().join("square.ele"),
+                Some(&dir.path().join("square.poly")),
+            );
+            match result {
+                Err(MeshError::Parse { line, .. }) => line,
+                _ => panic!("expected a parse error"),
+            }
+        };
+
+        assert_eq!(error_line(&NODE.replace("4 2 0 1", "4 3 0 1"), ELE, POLY), 2);
+        assert_eq!(error_line(&NODE.replace("2 1 1 2", "7 1 1 2"), ELE, POLY), 5);
+        assert_eq!(error_line(NODE, &ELE.replace("1 0 2 3", "1 0 2 4"), POLY), 3);
+        assert_eq!(error_line(NODE, ELE, &POLY.replace("3 3 0 2", "3 3 0")), 6);
+
+        // Counts which run past the end of the file, or overflow the number of fields
+        let huge = "18446744073709551615";
+        assert_eq!(error_line(&NODE.replace("4 2 0 1", &format!("{} 2 0 1", huge)), ELE, POLY), 2);
+        let attributes = NODE.replace("4 2 0 1", &format!("4 2 {} 1", huge));
+        assert_eq!(error_line(&attributes, ELE, POLY), 2);
+        assert_eq!(error_line(NODE, &ELE.replace("2 3 0", "3 3 0"), POLY), 1);
+        assert_eq!(error_line(NODE, &ELE.replace("2 3 0", &format!("2 3 {}", huge)), POLY), 1);
+        assert_eq!(error_line(NODE, ELE, &POLY.replace("4 1\n", "9 1\n")), 2);
+    }
+}
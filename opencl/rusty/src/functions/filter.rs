@@ -0,0 +1,87 @@
+extern crate rulinalg;
+
+use self::rulinalg::matrix::Matrix;
+
+// A filter matrix together with the number of time steps between its applications.
+#[derive(Debug)]
+pub struct Filter {
+    matrix: Matrix<f64>,
+    interval: i32,
+}
+
+impl Filter {
+    pub fn new(matrix: Matrix<f64>, interval: i32) -> Filter {
+        assert!(
+            interval > 0,
+            "filtering must be at least one step apart, not {}",
+            interval
+        );
+        Filter { matrix, interval }
+    }
+
+    pub fn matrix(&self) -> &Matrix<f64> {
+        &self.matrix
+    }
+
+    // Whether to apply the filter once `steps` steps have been taken.
+    pub fn is_due(&self, steps: i32) -> bool {
+        steps % self.interval == 0
+    }
+}
+
+// The exponential filter V * diag(sigma) * V^-1 for the Vandermonde matrix `v` of a modal
+// basis of degree n, where `mode_orders` holds the total degree of each of its modes in the
+// order of the columns of `v`. `order` must be even.
+pub fn exponential_filter(
+    v: &Matrix<f64>,
+    v_inv: &Matrix<f64>,
+    mode_orders: &[i32],
+    n: i32,
+    cutoff: i32,
+    order: i32,
+) -> Matrix<f64> {
+    assert!(
+        order > 0 && order % 2 == 0,
+        "the filter order must be positive and even, not {}",
+        order
+    );
+    let sigma: Vec<f64> = mode_orders
+        .iter()
+        .map(|&mode_order| exponential_filter_weight(mode_order, n, cutoff, order))
+        .collect();
+    v * Matrix::from_diag(&sigma) * v_inv
+}
+
+// The weight of the exponential filter sigma(eta) = exp(-alpha * eta^order) for a mode of
+// total degree `mode_order` in a basis of degree n, where eta = (mode_order - cutoff) / (n -
+// cutoff). Modes up to `cutoff` are left untouched, and alpha is chosen so that the highest
+// mode is damped to machine precision.
+pub fn exponential_filter_weight(mode_order: i32, n: i32, cutoff: i32, order: i32) -> f64 {
+    if mode_order <= cutoff {
+        return 1.;
+    }
+    let alpha = -(::std::f64::EPSILON.ln());
+    let eta = (mode_order - cutoff) as f64 / (n - cutoff) as f64;
+    (-alpha * eta.powi(order)).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::{exponential_filter, Filter};
+    use rulinalg::matrix::Matrix;
+
+    #[test]
+    #[should_panic]
+    fn test_filter_without_interval() {
+        Filter::new(Matrix::<f64>::identity(3), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_odd_order() {
+        let identity = Matrix::<f64>::identity(3);
+        exponential_filter(&identity, &identity, &[0, 1, 2], 2, 0, 3);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod filter;
 pub mod gamma;
 pub mod jacobi_polynomials;
 pub mod range_kutta;
@@ -1,91 +1,66 @@
 extern crate rulinalg;
 
 use self::rulinalg::vector::Vector;
-use functions::range_kutta::{RKA, RKB, RKC};
 use galerkin_1d::flux::FluxEnum;
 use galerkin_1d::flux::FluxScheme;
 use galerkin_1d::flux::FreeflowFlux;
 use galerkin_1d::flux::LaxFriedrichs;
 use galerkin_1d::galerkin::compute_flux;
+use galerkin_1d::galerkin::flux_divergence;
 use galerkin_1d::galerkin::Formulation;
 use galerkin_1d::galerkin::GalerkinScheme;
 use galerkin_1d::grid;
 use galerkin_1d::grid::FaceType;
-use galerkin_1d::grid::{generate_grid, ReferenceElement};
+use galerkin_1d::grid::{cfl_time_step, generate_grid, ReferenceElement};
+use galerkin_1d::norms::error_norms;
 use galerkin_1d::operators::assemble_operators;
 use galerkin_1d::operators::Operators;
-use galerkin_1d::unknowns::{communicate, initialize_storage, Unknown};
+use galerkin_1d::time_stepping::{integrate, LowStorageRK45, TimeStepper};
+use galerkin_1d::unknowns::{initialize_storage, Components, Unknown};
 use plot::plot2d::Plotter2D;
 use std::f64::consts;
-use std::iter::repeat;
+use std::ops::{Add, Mul};
 
 #[inline(never)]
-pub fn advec_1d<Fx>(
+pub fn advec_1d<Fx, TS>(
     u_0: Fx,
     grid: &Grid,
     reference_element: &ReferenceElement,
     operators: &Operators,
-    a: f64,
+    stepper: &mut TS,
 ) -> Vec<UStorage>
 where
     Fx: Fn(&Vector<f64>) -> U,
+    TS: TimeStepper<U>,
 {
     let mut plotter = Plotter2D::create(0.0, 2.0, -1.0, 1.0);
 
     let final_time = 1.3;
 
     let cfl = 0.75;
-    let x_scale = 0.01;
-    let dt: f64 = 0.5 * cfl / (consts::PI * 2.) * x_scale;
-    let n_t = (final_time / dt).ceil() as i32;
-    let dt = final_time / n_t as f64;
-
-    let mut t: f64 = 0.0;
+    let dt: f64 = cfl_time_step(grid, reference_element, max_speed(grid), cfl);
 
     let mut storage: Vec<UStorage> =
         initialize_storage(u_0, reference_element.n_p, grid, operators);
-    let mut residuals: Vec<Vector<f64>> = repeat(Vector::zeros(reference_element.n_p as usize + 1))
-        .take(grid.elements.len())
-        .collect();
-
-    for epoch in 0..n_t {
-        for int_rk in 0..5 {
-            let t = t + RKC[int_rk] * dt;
-
-            // communicate current values of u across faces
-            communicate(t, grid, &storage);
-
-            // update each element's local solution
-            for elt in (*grid).elements.iter() {
-                let mut storage = &mut storage[elt.index as usize];
-
-                let residuals_u = {
-                    let residuals_u = &(residuals[elt.index as usize]);
 
-                    let rhs_u = advec_rhs_1d(&elt, &storage, &operators, a);
-                    residuals_u * RKA[int_rk] + rhs_u * dt
-                };
-
-                let new_u = {
-                    let u_ref: &U = &storage.u_k;
-                    &u_ref.u + &residuals_u * RKB[int_rk]
-                };
-
-                residuals[elt.index as usize] = residuals_u;
-
-                storage.u_k = U { u: new_u };
-            }
-        }
-        if epoch % 20 == 0 {
-            plotter.header();
-            for elt in (*grid).elements.iter() {
-                let storage = &storage[elt.index as usize];
-                plotter.plot(&elt.x_k, &storage.u_k.u);
+    integrate(
+        final_time,
+        dt,
+        grid,
+        &mut storage,
+        stepper,
+        |elt, elt_storage, _| advec_rhs_1d(elt, elt_storage, operators),
+        |epoch, _, storage| {
+            if epoch % 20 == 0 {
+                plotter.header();
+                for elt in (*grid).elements.iter() {
+                    let storage = &storage[elt.index as usize];
+                    plotter.plot(&elt.x_k, &storage.u_k.u);
+                }
+                plotter.replot();
             }
-            plotter.replot();
-        }
-        t = t + dt;
-    }
+        },
+    );
     for elt in (*grid).elements.iter() {
         let storage = &storage[elt.index as usize];
         println!("{:?}", &storage.u_k);
@@ -94,12 +69,17 @@ where
     storage
 }
 
-fn advec_rhs_1d(
-    elt: &Element,
+// The right-hand side of u_t + (a(x) u)_x = 0, with the speed taken from the element's
+// spatial flux. Generic over the scheme so that variants of Advec, e.g. with a source term,
+// can share it.
+fn advec_rhs_1d<GS>(
+    elt: &grid::Element<GS>,
     elt_storage: &UStorage,
     operators: &Operators,
-    a: f64,
-) -> Vector<f64> {
+) -> U
+where
+    GS: GalerkinScheme<U = U, F = LinearFlux>,
+{
     let (du_left, du_right) = compute_flux(elt, elt_storage);
     //    let du_left = {
     //        let u_h = elt_storage.u_left_minus.get();
@@ -122,13 +102,9 @@ fn advec_rhs_1d(
     //        (((a * u_h) - numerical_flux) * elt.right_outward_normal)
     //    };
     let du: Vector<f64> = vector![du_left, du_right];
-    let dr_u = &operators.d_r * &elt_storage.u_k.u;
-    let a_rx = &elt_storage.r_x * (-a);
-    let rhs_u = &a_rx.elemul(&dr_u);
-    let scaled_du = &elt_storage.r_x_at_faces.elemul(&du);
-    let lifted_flux = &operators.lift * scaled_du;
-    let result = rhs_u + lifted_flux;
-    result
+    let f = elt.spatial_flux.a.elemul(&elt_storage.u_k.u);
+    let result = flux_divergence(elt, elt_storage, operators, &f, &du);
+    U { u: result }
 }
 
 fn lax_friedrichs(a: f64, u_minus: f64, u_plus: f64, outward_normal: f64) -> f64 {
@@ -139,11 +115,27 @@ fn lax_friedrichs(a: f64, u_minus: f64, u_plus: f64, outward_normal: f64) -> f64
     avg + a * jump / 2.
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct U {
     u: Vector<f64>,
 }
 
+impl Add for U {
+    type Output = U;
+
+    fn add(self, other: U) -> U {
+        U { u: self.u + other.u }
+    }
+}
+
+impl Mul<f64> for U {
+    type Output = U;
+
+    fn mul(self, other: f64) -> U {
+        U { u: self.u * other }
+    }
+}
+
 impl Unknown for U {
     type Unit = f64;
 
@@ -160,23 +152,52 @@ impl Unknown for U {
     }
 }
 
+impl Components for U {
+    fn components(&self) -> Vec<&Vector<f64>> {
+        vec![&self.u]
+    }
+
+    fn from_components(mut components: Vec<Vector<f64>>) -> U {
+        U {
+            u: components.remove(0),
+        }
+    }
+}
+
 type UStorage = grid::ElementStorage<U, LinearFlux>;
 
 type Grid = grid::Grid<Advec>;
 
 type Element = grid::Element<Advec>;
 
-type LinearFlux = f64;
+// The advection speed a(x) at the nodes of an element. The upwind direction on each face
+// is decided by the speeds on either side of it, so a may change sign within the domain.
+#[derive(Clone, Debug)]
+pub struct LinearFlux {
+    pub a: Vector<f64>,
+}
+
+impl LinearFlux {
+    // The speed `a` sampled at the nodes `xs`.
+    pub fn sample<Fa>(xs: &Vector<f64>, a: Fa) -> LinearFlux
+    where
+        Fa: Fn(f64) -> f64,
+    {
+        LinearFlux {
+            a: xs.iter().map(|&x| a(x)).collect(),
+        }
+    }
+}
 
 impl grid::SpatialFlux for LinearFlux {
     type Unit = f64;
 
     fn first(&self) -> Self::Unit {
-        *self
+        self.a[0]
     }
 
     fn last(&self) -> Self::Unit {
-        *self
+        self.a[self.a.size() - 1]
     }
 
     fn zero() -> Self::Unit {
@@ -184,6 +205,17 @@ impl grid::SpatialFlux for LinearFlux {
     }
 }
 
+// The largest advection speed anywhere in the grid.
+fn max_speed<GS>(grid: &grid::Grid<GS>) -> f64
+where
+    GS: GalerkinScheme<F = LinearFlux>,
+{
+    grid.elements
+        .iter()
+        .flat_map(|elt| elt.spatial_flux.a.iter())
+        .fold(0., |acc: f64, a| acc.max(a.abs()))
+}
+
 pub struct AdvecFluxScheme {}
 
 impl FluxScheme<U, LinearFlux> for AdvecFluxScheme {
@@ -214,7 +246,7 @@ pub fn advec_1d_example() -> (Vec<f64>, Vec<f64>) {
     let a = consts::PI * 2.;
     let left_boundary_face = grid::Face {
         face_type: FaceType::Boundary(Box::new(move |t: f64, _| -(a * t).sin()), a),
-        flux: FluxEnum::Left(LaxFriedrichs { alpha: 1. }),
+        flux: FluxEnum::Left(LaxFriedrichs { alpha: 0. }),
     };
     let right_boundary_face = grid::Face {
         face_type: grid::freeFlowBoundary(a),
@@ -227,13 +259,23 @@ pub fn advec_1d_example() -> (Vec<f64>, Vec<f64>) {
         &reference_element,
         left_boundary_face,
         right_boundary_face,
-        LaxFriedrichs { alpha: 1. },
-        move |_| a,
+        LaxFriedrichs { alpha: 0. },
+        |xs| LinearFlux::sample(xs, |_| a),
     );
 
     let operators = assemble_operators::<U>(&reference_element);
 
-    let storages = advec_1d(&u_0, &grid, &reference_element, &operators, a);
+    let storages = advec_1d(
+        &u_0,
+        &grid,
+        &reference_element,
+        &operators,
+        &mut LowStorageRK45::new(),
+    );
+
+    // The inflow boundary condition carries the initial wave along, u = sin(x - a t).
+    let norms = error_norms(&grid, &storages, &operators, 0, |x, t| (x - a * t).sin(), 1.3);
+    println!("{:?}", norms);
 
     let mut xs: Vec<f64> = vec![];
     for elt in grid.elements.iter() {
@@ -251,10 +293,321 @@ pub fn advec_1d_example() -> (Vec<f64>, Vec<f64>) {
 mod tests {
     extern crate gnuplot;
 
+    use super::{advec_rhs_1d, max_speed, Advec, AdvecFluxScheme, Grid, LinearFlux, U};
     use galerkin_1d::advec::advec_1d_example;
+    use galerkin_1d::flux::LaxFriedrichs;
+    use galerkin_1d::galerkin::{Formulation, GalerkinScheme};
+    use galerkin_1d::grid;
+    use galerkin_1d::grid::{cfl_time_step, generate_periodic_grid, ReferenceElement};
+    use galerkin_1d::limiters::SlopeLimiter;
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::time_stepping::{integrate, integrate_with_limiter, LowStorageRK45, SSPRK3};
+    use galerkin_1d::unknowns::initialize_storage;
+    use rulinalg::vector::Vector;
+    use std::f64::consts;
 
     #[test]
     fn test() {
         let (xs, us) = advec_1d_example();
     }
+
+    #[test]
+    fn test_periodic_advection() {
+        // After one period, the wave should return to where it started.
+        let n_p = 6;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let a = 1.;
+        let grid: Grid = generate_periodic_grid(
+            0.0,
+            2.0,
+            10,
+            &reference_element,
+            LaxFriedrichs { alpha: 0. },
+            |xs| LinearFlux::sample(xs, |_| a),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let u_0 = |xs: &Vector<f64>| U {
+            u: xs.iter().map(|x| (consts::PI * x).sin()).collect(),
+        };
+        let mut storages = initialize_storage(&u_0, n_p, &grid, &operators);
+        let dt = cfl_time_step(&grid, &reference_element, a, 0.75);
+        integrate(
+            2.0,
+            dt,
+            &grid,
+            &mut storages,
+            &mut LowStorageRK45::new(),
+            |elt, elt_storage, _| advec_rhs_1d(elt, elt_storage, &operators),
+            |_, _, _| {},
+        );
+        for (elt, storage) in grid.elements.iter().zip(storages.iter()) {
+            let expected = u_0(&elt.x_k);
+            for (u, u_exact) in storage.u_k.u.iter().zip(expected.u.iter()) {
+                assert!((u - u_exact).abs() < 1e-5);
+            }
+        }
+    }
+
+    // Plain advection in the weak form, to check it against the strong form of Advec.
+    struct WeakAdvec {}
+
+    impl GalerkinScheme for WeakAdvec {
+        type U = U;
+        type F = LinearFlux;
+        type FS = AdvecFluxScheme;
+
+        const FORMULATION: Formulation = Formulation::Weak;
+    }
+
+    // The largest nodal error after advecting sin(pi x) at unit speed for part of a period
+    // around [0, 2], so that the exact solution sin(pi (x - t)) is not the initial state.
+    fn periodic_advection_error<GS>() -> f64
+    where
+        GS: GalerkinScheme<U = U, F = LinearFlux, FS = AdvecFluxScheme>,
+    {
+        let n_p = 6;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let a = 1.;
+        let grid: grid::Grid<GS> = generate_periodic_grid(
+            0.0,
+            2.0,
+            10,
+            &reference_element,
+            LaxFriedrichs { alpha: 0. },
+            |xs| LinearFlux::sample(xs, |_| a),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let u_0 = |xs: &Vector<f64>| U {
+            u: xs.iter().map(|x| (consts::PI * x).sin()).collect(),
+        };
+        let mut storages = initialize_storage(&u_0, n_p, &grid, &operators);
+        let dt = cfl_time_step(&grid, &reference_element, a, 0.75);
+        let final_time = 0.7;
+        integrate(
+            final_time,
+            dt,
+            &grid,
+            &mut storages,
+            &mut LowStorageRK45::new(),
+            |elt, elt_storage, _| advec_rhs_1d(elt, elt_storage, &operators),
+            |_, _, _| {},
+        );
+        grid.elements
+            .iter()
+            .zip(storages.iter())
+            .flat_map(|(elt, storage)| {
+                elt.x_k
+                    .iter()
+                    .zip(storage.u_k.u.iter())
+                    .map(|(x, u)| (u - (consts::PI * (x - final_time)).sin()).abs())
+                    .collect::<Vec<f64>>()
+            })
+            .fold(0., f64::max)
+    }
+
+    #[test]
+    fn test_weak_formulation() {
+        // The two forms are the same scheme on paper, so both runs should match the exact
+        // solution, and each other, to within the discretisation error.
+        let strong_error = periodic_advection_error::<Advec>();
+        let weak_error = periodic_advection_error::<WeakAdvec>();
+        assert!(strong_error < 1e-5, "strong error {}", strong_error);
+        assert!(weak_error < 1e-5, "weak error {}", weak_error);
+        assert!((strong_error - weak_error).abs() < 1e-6);
+    }
+
+    // Advection with the forcing S = a cos(x) cos(t) - sin(x) sin(t), which makes
+    // u = sin(x) cos(t) the exact solution.
+    struct ForcedAdvec {}
+
+    impl GalerkinScheme for ForcedAdvec {
+        type U = U;
+        type F = LinearFlux;
+        type FS = AdvecFluxScheme;
+
+        const FORMULATION: Formulation = Formulation::Strong;
+
+        fn source(xs: &Vector<f64>, t: f64, _: &U) -> Option<U> {
+            let a = 1.;
+            Some(U {
+                u: xs
+                    .iter()
+                    .map(|x| a * x.cos() * t.cos() - x.sin() * t.sin())
+                    .collect(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_manufactured_solution() {
+        let n_p = 6;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let a = 1.;
+        let grid: grid::Grid<ForcedAdvec> = generate_periodic_grid(
+            0.0,
+            2. * consts::PI,
+            10,
+            &reference_element,
+            LaxFriedrichs { alpha: 0. },
+            |xs| LinearFlux::sample(xs, |_| a),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let u_0 = |xs: &Vector<f64>| U {
+            u: xs.iter().map(|x| x.sin()).collect(),
+        };
+        let mut storages = initialize_storage(&u_0, n_p, &grid, &operators);
+        let dt = cfl_time_step(&grid, &reference_element, a, 0.75);
+        let final_time = 1.5;
+        integrate(
+            final_time,
+            dt,
+            &grid,
+            &mut storages,
+            &mut LowStorageRK45::new(),
+            |elt, elt_storage, _| advec_rhs_1d(elt, elt_storage, &operators),
+            |_, _, _| {},
+        );
+        for (elt, storage) in grid.elements.iter().zip(storages.iter()) {
+            for (x, u) in elt.x_k.iter().zip(storage.u_k.u.iter()) {
+                assert!((u - x.sin() * final_time.cos()).abs() < 1e-5);
+            }
+        }
+    }
+
+    // Advection with the speed a(x) = sin(x), which changes sign at 0 and pi, forced so that
+    // u = 2 + cos(x - t) is the exact solution of u_t + (a u)_x = S.
+    struct VariableSpeedAdvec {}
+
+    impl GalerkinScheme for VariableSpeedAdvec {
+        type U = U;
+        type F = LinearFlux;
+        type FS = AdvecFluxScheme;
+
+        const FORMULATION: Formulation = Formulation::Strong;
+
+        fn source(xs: &Vector<f64>, t: f64, _: &U) -> Option<U> {
+            Some(U {
+                u: xs
+                    .iter()
+                    .map(|&x| {
+                        (x - t).sin() + x.cos() * (2. + (x - t).cos())
+                            - x.sin() * (x - t).sin()
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_variable_speed() {
+        let n_p = 6;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let grid: grid::Grid<VariableSpeedAdvec> = generate_periodic_grid(
+            0.0,
+            2. * consts::PI,
+            12,
+            &reference_element,
+            LaxFriedrichs { alpha: 0. },
+            |xs| LinearFlux::sample(xs, f64::sin),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let u_0 = |xs: &Vector<f64>| U {
+            u: xs.iter().map(|x| 2. + x.cos()).collect(),
+        };
+        let mut storages = initialize_storage(&u_0, n_p, &grid, &operators);
+        let dt = cfl_time_step(&grid, &reference_element, max_speed(&grid), 0.75);
+        let final_time = 2.;
+        integrate(
+            final_time,
+            dt,
+            &grid,
+            &mut storages,
+            &mut LowStorageRK45::new(),
+            |elt, elt_storage, _| advec_rhs_1d(elt, elt_storage, &operators),
+            |_, _, _| {},
+        );
+        for (elt, storage) in grid.elements.iter().zip(storages.iter()) {
+            for (x, u) in elt.x_k.iter().zip(storage.u_k.u.iter()) {
+                assert!((u - (2. + (x - final_time).cos())).abs() < 1e-4);
+            }
+        }
+    }
+
+    // Advect u_0 once around the periodic domain [0, 2] with the given limiter, returning the
+    // final solution at every node alongside u_0 there.
+    fn limited_advection<Fx>(u_0: Fx, limiter: SlopeLimiter) -> Vec<(f64, f64)>
+    where
+        Fx: Fn(&Vector<f64>) -> U,
+    {
+        let n_p = 2;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let a = 1.;
+        let grid: Grid = generate_periodic_grid(
+            0.0,
+            2.0,
+            40,
+            &reference_element,
+            LaxFriedrichs { alpha: 0. },
+            |xs| LinearFlux::sample(xs, |_| a),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let mut storages = initialize_storage(&u_0, n_p, &grid, &operators);
+        let dt = cfl_time_step(&grid, &reference_element, a, 0.2);
+        integrate_with_limiter(
+            2.0,
+            dt,
+            &grid,
+            &mut storages,
+            &mut SSPRK3 {},
+            |elt, elt_storage, _| advec_rhs_1d(elt, elt_storage, &operators),
+            |grid, storages| limiter.limit(grid, storages, &operators),
+            |_, _, _| {},
+        );
+        grid.elements
+            .iter()
+            .zip(storages.iter())
+            .flat_map(|(elt, storage)| {
+                storage
+                    .u_k
+                    .u
+                    .iter()
+                    .cloned()
+                    .zip(u_0(&elt.x_k).u.iter().cloned())
+                    .collect::<Vec<(f64, f64)>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_limited_square_wave() {
+        // The minmod-limited solution must not overshoot the initial bounds.
+        let square_wave = |xs: &Vector<f64>| U {
+            u: xs
+                .iter()
+                .map(|&x| if x > 0.5 && x < 1.0 { 1. } else { 0. })
+                .collect(),
+        };
+        for (u, _) in limited_advection(&square_wave, SlopeLimiter::Minmod) {
+            assert!(u > -1e-8 && u < 1. + 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_tvb_limiter_keeps_smooth_extrema() {
+        // With M above the curvature of the wave, the TVB limiter should never engage,
+        // while plain minmod clips the extrema.
+        let sine = |xs: &Vector<f64>| U {
+            u: xs.iter().map(|x| (consts::PI * x).sin()).collect(),
+        };
+        let max_error = |limiter| {
+            limited_advection(&sine, limiter)
+                .iter()
+                .map(|&(u, u_exact)| (u - u_exact).abs())
+                .fold(0., f64::max)
+        };
+        let tvb_error = max_error(SlopeLimiter::TVB { m: 20. });
+        let minmod_error = max_error(SlopeLimiter::Minmod);
+        assert!(tvb_error < 1e-3);
+        assert!(minmod_error > 2. * tvb_error);
+    }
 }
@@ -0,0 +1,439 @@
+extern crate rulinalg;
+
+use self::rulinalg::vector::Vector;
+use functions::filter::Filter;
+use galerkin_1d::diffusion::{diffusive_time_step, Diffusion, DiffusionFlux};
+use galerkin_1d::flux::FluxScheme;
+use galerkin_1d::flux::NumericalFlux;
+use galerkin_1d::flux::Side;
+use galerkin_1d::galerkin::compute_flux;
+use galerkin_1d::galerkin::flux_divergence;
+use galerkin_1d::galerkin::Formulation;
+use galerkin_1d::galerkin::GalerkinScheme;
+use galerkin_1d::grid;
+use galerkin_1d::grid::{cfl_time_step, generate_periodic_grid, ReferenceElement};
+use galerkin_1d::limiters::SlopeLimiter;
+use galerkin_1d::operators::assemble_operators;
+use galerkin_1d::operators::Operators;
+use galerkin_1d::time_stepping::{integrate_operator, integrate_with_limiter, SSPRK3, TimeStepper};
+use galerkin_1d::unknowns::{apply_filter, initialize_storage, Components, Unknown};
+use std::f64::consts;
+use std::ops::{Add, Mul};
+
+// The inviscid Burgers equation u_t + (u^2 / 2)_x = 0.
+pub fn burgers_1d<Fx, TS>(
+    u_0: Fx,
+    grid: &Grid,
+    reference_element: &ReferenceElement,
+    operators: &Operators,
+    final_time: f64,
+    stepper: &mut TS,
+    limiter: Option<SlopeLimiter>,
+    filter: Option<&Filter>,
+) -> Vec<UStorage>
+where
+    Fx: Fn(&Vector<f64>) -> U,
+    TS: TimeStepper<U>,
+{
+    let mut storage: Vec<UStorage> =
+        initialize_storage(u_0, reference_element.n_p, grid, operators);
+
+    // The characteristic speed is u itself, and the maximum principle keeps |u| bounded by
+    // its initial maximum.
+    let max_speed = storage
+        .iter()
+        .flat_map(|storage| storage.u_k.u.iter())
+        .fold(0., |acc: f64, u| acc.max(u.abs()));
+    // A state which is zero everywhere never moves, and has no speed to set a time step by.
+    if max_speed == 0. {
+        return storage;
+    }
+    let cfl = 0.5;
+    let dt: f64 = cfl_time_step(grid, reference_element, max_speed, cfl);
+
+    integrate_with_limiter(
+        final_time,
+        dt,
+        grid,
+        &mut storage,
+        stepper,
+        |elt, elt_storage, _| burgers_rhs_1d(elt, elt_storage, operators),
+        |grid, storages| {
+            if let Some(limiter) = limiter {
+                limiter.limit(grid, storages, operators);
+            }
+        },
+        |epoch, _, storages| {
+            if let Some(filter) = filter {
+                if filter.is_due(epoch + 1) {
+                    apply_filter(filter.matrix(), storages);
+                }
+            }
+        },
+    );
+
+    storage
+}
+
+// The viscous Burgers equation u_t + (u^2 / 2)_x = nu u_xx, with the viscous term
+// discretized by LDG.
+pub fn viscous_burgers_1d<Fx, TS>(
+    u_0: Fx,
+    grid: &Grid,
+    reference_element: &ReferenceElement,
+    operators: &Operators,
+    nu: f64,
+    flux: DiffusionFlux,
+    final_time: f64,
+    stepper: &mut TS,
+) -> Vec<UStorage>
+where
+    Fx: Fn(&Vector<f64>) -> U,
+    TS: TimeStepper<U>,
+{
+    let mut storage: Vec<UStorage> =
+        initialize_storage(u_0, reference_element.n_p, grid, operators);
+
+    let max_speed = storage
+        .iter()
+        .flat_map(|storage| storage.u_k.u.iter())
+        .fold(0., |acc: f64, u| acc.max(u.abs()));
+    // Viscosity alone limits the time step for a state which is zero everywhere.
+    let diffusive_dt = diffusive_time_step(grid, reference_element, nu, 0.05);
+    let dt: f64 = if max_speed > 0. {
+        cfl_time_step(grid, reference_element, max_speed, 0.5).min(diffusive_dt)
+    } else {
+        diffusive_dt
+    };
+
+    let operator = Diffusion {
+        hyperbolic: |elt: &Element, elt_storage: &UStorage, _| {
+            burgers_rhs_1d(elt, elt_storage, operators)
+        },
+        nu,
+        flux,
+        operators,
+    };
+
+    integrate_operator(
+        final_time,
+        dt,
+        grid,
+        &mut storage,
+        stepper,
+        &operator,
+        |_, _| {},
+        |_, _, _| {},
+    );
+
+    storage
+}
+
+fn burgers_rhs_1d(elt: &Element, elt_storage: &UStorage, operators: &Operators) -> U {
+    let (du_left, du_right) = compute_flux(elt, elt_storage);
+    let du: Vector<f64> = vector![du_left, du_right];
+    let f = elt_storage.u_k.u.elemul(&elt_storage.u_k.u) / 2.;
+    U {
+        u: flux_divergence(elt, elt_storage, operators, &f, &du),
+    }
+}
+
+fn burgers_flux(u: f64) -> f64 {
+    u * u / 2.
+}
+
+// The numerical fluxes available for Burgers' equation. Each returns the strong-form face
+// term n * (f(u-) - f*), where f* is the numerical flux.
+#[derive(Clone, Copy)]
+pub enum BurgersFlux {
+    // The local Lax-Friedrichs (Rusanov) flux, which adds dissipation proportional to the
+    // largest wave speed on either side of the face.
+    LocalLaxFriedrichs,
+
+    // The exact Riemann solver for the convex flux u^2 / 2.
+    Godunov,
+}
+
+impl NumericalFlux<U, ()> for BurgersFlux {
+    fn flux(&self, minus: Side<U, ()>, plus: Side<U, ()>, outward_normal: f64) -> f64 {
+        let (u_minus, u_plus) = (minus.u, plus.u);
+        let n_f_star = match *self {
+            BurgersFlux::LocalLaxFriedrichs => {
+                let c = u_minus.abs().max(u_plus.abs());
+                let avg = (burgers_flux(u_minus) + burgers_flux(u_plus)) / 2.;
+                outward_normal * avg + c / 2. * (u_minus - u_plus)
+            }
+            BurgersFlux::Godunov => {
+                // Orient the Riemann problem so that u_l is on the left of the face.
+                let (u_l, u_r) = if outward_normal > 0. {
+                    (u_minus, u_plus)
+                } else {
+                    (u_plus, u_minus)
+                };
+                let f_star = if u_l <= u_r {
+                    // rarefaction: minimize f over [u_l, u_r]
+                    if u_l > 0. {
+                        burgers_flux(u_l)
+                    } else if u_r < 0. {
+                        burgers_flux(u_r)
+                    } else {
+                        0.
+                    }
+                } else {
+                    // shock: maximize f over [u_r, u_l]
+                    burgers_flux(u_l).max(burgers_flux(u_r))
+                };
+                outward_normal * f_star
+            }
+        };
+        outward_normal * burgers_flux(u_minus) - n_f_star
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct U {
+    u: Vector<f64>,
+}
+
+impl Add for U {
+    type Output = U;
+
+    fn add(self, other: U) -> U {
+        U { u: self.u + other.u }
+    }
+}
+
+impl Mul<f64> for U {
+    type Output = U;
+
+    fn mul(self, other: f64) -> U {
+        U { u: self.u * other }
+    }
+}
+
+impl Unknown for U {
+    type Unit = f64;
+
+    fn first(&self) -> f64 {
+        self.u[0]
+    }
+
+    fn last(&self) -> f64 {
+        self.u[self.u.size() - 1]
+    }
+
+    fn zero() -> f64 {
+        0.0
+    }
+}
+
+impl Components for U {
+    fn components(&self) -> Vec<&Vector<f64>> {
+        vec![&self.u]
+    }
+
+    fn from_components(mut components: Vec<Vector<f64>>) -> U {
+        U {
+            u: components.remove(0),
+        }
+    }
+}
+
+type UStorage = grid::ElementStorage<U, ()>;
+
+type Grid = grid::Grid<Burgers>;
+
+type Element = grid::Element<Burgers>;
+
+pub struct BurgersFluxScheme {}
+
+impl FluxScheme<U, ()> for BurgersFluxScheme {
+    type Left = BurgersFlux;
+    type Right = BurgersFlux;
+    type Interior = BurgersFlux;
+}
+
+pub struct Burgers {}
+
+impl GalerkinScheme for Burgers {
+    type U = U;
+    type F = ();
+    type FS = BurgersFluxScheme;
+
+    const FORMULATION: Formulation = Formulation::Strong;
+}
+
+fn u_0(xs: &Vector<f64>) -> U {
+    U {
+        u: xs.iter().map(|x: &f64| (consts::PI * x).sin()).collect(),
+    }
+}
+
+pub fn burgers_1d_example() {
+    let n_p = 4;
+    let reference_element = ReferenceElement::legendre(n_p);
+    let grid: Grid = generate_periodic_grid(
+        0.0,
+        2.0,
+        20,
+        &reference_element,
+        BurgersFlux::LocalLaxFriedrichs,
+        |_| (),
+    );
+    let operators = assemble_operators::<U>(&reference_element);
+
+    // The sine wave steepens into a shock at t = 1 / pi.
+    let storages = burgers_1d(
+        &u_0,
+        &grid,
+        &reference_element,
+        &operators,
+        0.3,
+        &mut SSPRK3 {},
+        None,
+        None,
+    );
+
+    for storage in storages.iter() {
+        println!("{:?}", &storage.u_k);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::{burgers_1d, u_0, viscous_burgers_1d, BurgersFlux, Grid, U};
+    use galerkin_1d::diffusion::DiffusionFlux;
+    use galerkin_1d::grid::{generate_periodic_grid, ReferenceElement};
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::time_stepping::SSPRK3;
+    use rulinalg::vector::Vector;
+    use std::f64::consts;
+
+    // Solve u = sin(pi (x - u t)) along the characteristics by Newton's method.
+    fn exact_solution(x: f64, t: f64) -> f64 {
+        let mut u = (consts::PI * x).sin();
+        for _ in 0..50 {
+            let phase = consts::PI * (x - u * t);
+            let g = u - phase.sin();
+            let dg = 1. + consts::PI * t * phase.cos();
+            u = u - g / dg;
+        }
+        u
+    }
+
+    fn smooth_solution_error(flux: BurgersFlux) -> f64 {
+        let n_p = 4;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let grid: Grid = generate_periodic_grid(0.0, 2.0, 20, &reference_element, flux, |_| ());
+        let operators = assemble_operators::<U>(&reference_element);
+        let t = 0.2;
+        let storages = burgers_1d(
+            &u_0,
+            &grid,
+            &reference_element,
+            &operators,
+            t,
+            &mut SSPRK3 {},
+            None,
+            None,
+        );
+        grid.elements
+            .iter()
+            .zip(storages.iter())
+            .flat_map(|(elt, storage)| {
+                elt.x_k
+                    .iter()
+                    .zip(storage.u_k.u.iter())
+                    .map(|(&x, &u)| (u - exact_solution(x, t)).abs())
+                    .collect::<Vec<f64>>()
+            })
+            .fold(0., f64::max)
+    }
+
+    #[test]
+    fn test_local_lax_friedrichs_before_shock() {
+        assert!(smooth_solution_error(BurgersFlux::LocalLaxFriedrichs) < 1e-3);
+    }
+
+    #[test]
+    fn test_godunov_before_shock() {
+        assert!(smooth_solution_error(BurgersFlux::Godunov) < 1e-3);
+    }
+
+    #[test]
+    fn test_zero_state() {
+        let n_p = 4;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let grid: Grid = generate_periodic_grid(
+            0.0,
+            2.0,
+            20,
+            &reference_element,
+            BurgersFlux::LocalLaxFriedrichs,
+            |_| (),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let zero = |xs: &Vector<f64>| U {
+            u: Vector::zeros(xs.size()),
+        };
+        let inviscid = burgers_1d(
+            &zero,
+            &grid,
+            &reference_element,
+            &operators,
+            0.3,
+            &mut SSPRK3 {},
+            None,
+            None,
+        );
+        let viscous = viscous_burgers_1d(
+            &zero,
+            &grid,
+            &reference_element,
+            &operators,
+            0.05,
+            DiffusionFlux::LDG { tau: 0. },
+            0.1,
+            &mut SSPRK3 {},
+        );
+        for storage in inviscid.iter().chain(viscous.iter()) {
+            assert!(storage.u_k.u.iter().all(|&u| u == 0.));
+        }
+    }
+
+    #[test]
+    fn test_viscous_burgers_past_shock_time() {
+        // Without viscosity the sine wave breaks at t = 1 / pi. With it the front stays
+        // resolved and the solution obeys the maximum principle.
+        let n_p = 4;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let grid: Grid = generate_periodic_grid(
+            0.0,
+            2.0,
+            20,
+            &reference_element,
+            BurgersFlux::LocalLaxFriedrichs,
+            |_| (),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let storages = viscous_burgers_1d(
+            &u_0,
+            &grid,
+            &reference_element,
+            &operators,
+            0.05,
+            DiffusionFlux::LDG { tau: 0. },
+            0.6,
+            &mut SSPRK3 {},
+        );
+        let max_u = storages
+            .iter()
+            .flat_map(|storage| storage.u_k.u.iter())
+            .map(|u| u.abs())
+            .fold(0., |acc: f64, u| if u.is_nan() || u > acc { u } else { acc });
+        assert!(max_u < 1.01);
+        assert!(max_u > 0.5);
+    }
+}
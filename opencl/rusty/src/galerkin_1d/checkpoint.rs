@@ -0,0 +1,70 @@
+use checkpoint::file::Checkpoint;
+use galerkin_1d::grid::{ElementStorage, SpatialFlux};
+use galerkin_1d::time_stepping::TimeStepper;
+use galerkin_1d::unknowns::Components;
+use rulinalg::vector::Vector;
+use std::io;
+
+// The state of a run of order `order` after `step` steps of length `dt`, at time t.
+pub fn capture<U, F, TS>(
+    order: i32,
+    step: i32,
+    time: f64,
+    dt: f64,
+    storages: &Vec<ElementStorage<U, F>>,
+    stepper: &TS,
+) -> Checkpoint
+where
+    U: Components,
+    F: SpatialFlux,
+    TS: TimeStepper<U>,
+{
+    Checkpoint {
+        order,
+        step,
+        time,
+        dt,
+        solution: storages.iter().map(|storage| fields(&storage.u_k)).collect(),
+        residuals: stepper.residuals().iter().map(fields).collect(),
+    }
+}
+
+// Put the solution and the stepper's residuals back the way they were when `checkpoint` was
+// captured, and return the step and the time to resume from. The grid must be the one the
+// checkpoint was taken on, and the steps must be of the same length `dt`. A checkpoint of
+// another order or step, or with differently shaped elements, is rejected as invalid data,
+// leaving the solution alone.
+pub fn restore<U, F, TS>(
+    checkpoint: &Checkpoint,
+    order: i32,
+    dt: f64,
+    storages: &mut Vec<ElementStorage<U, F>>,
+    stepper: &mut TS,
+) -> io::Result<(i32, f64)>
+where
+    U: Components,
+    F: SpatialFlux,
+    TS: TimeStepper<U>,
+{
+    let solution: Vec<Vec<Vector<f64>>> =
+        storages.iter().map(|storage| fields(&storage.u_k)).collect();
+    checkpoint.check_layout(order, &solution)?;
+    checkpoint.check_time_step(dt)?;
+
+    for (storage, components) in storages.iter_mut().zip(checkpoint.solution.iter()) {
+        storage.u_k = U::from_components(components.clone());
+    }
+    stepper.set_residuals(
+        checkpoint
+            .residuals
+            .iter()
+            .map(|components| U::from_components(components.clone()))
+            .collect(),
+    );
+
+    Ok((checkpoint.step, checkpoint.time))
+}
+
+fn fields<U: Components>(u: &U) -> Vec<Vector<f64>> {
+    u.components().into_iter().cloned().collect()
+}
@@ -0,0 +1,87 @@
+use convergence::table::ConvergenceTable;
+use galerkin_1d::grid::ReferenceElement;
+use galerkin_1d::norms::ErrorNorms;
+
+// Run `solve` for every polynomial order in `orders` and every element count in
+// `element_counts`, and tabulate the errors it returns with the observed rates. The grid is
+// assumed to be uniform on an interval of the given length, so that h = length / n_k.
+pub fn convergence_study<F>(
+    orders: &[i32],
+    element_counts: &[i32],
+    length: f64,
+    mut solve: F,
+) -> ConvergenceTable
+where
+    F: FnMut(&ReferenceElement, i32) -> ErrorNorms,
+{
+    let mut table = ConvergenceTable::new();
+    for &n in orders.iter() {
+        let reference_element = ReferenceElement::legendre(n);
+        for &n_k in element_counts.iter() {
+            let norms = solve(&reference_element, n_k);
+            let h = length / n_k as f64;
+            table.push(n, n_k as usize, h, norms.l2, norms.l_inf, norms.h1_seminorm);
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::convergence_study;
+    use galerkin_1d::diffusion::DiffusionFlux;
+    use galerkin_1d::flux::FreeflowFlux;
+    use galerkin_1d::grid::{generate_periodic_grid, Grid};
+    use galerkin_1d::heat::{heat_1d, Heat, U};
+    use galerkin_1d::norms::error_norms;
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::time_stepping::ClassicalRK4;
+    use galerkin_1d::unknowns::Components;
+    use rulinalg::vector::Vector;
+    use std::f64::consts;
+
+    #[test]
+    fn test_heat_convergence() {
+        let length = 2. * consts::PI;
+        let t = 0.1;
+        let table = convergence_study(
+            &[1, 2],
+            &[10, 20, 40],
+            length,
+            |reference_element, n_k| {
+                let grid: Grid<Heat> = generate_periodic_grid(
+                    0.0,
+                    length,
+                    n_k,
+                    reference_element,
+                    FreeflowFlux {},
+                    |_| (),
+                );
+                let operators = assemble_operators::<U>(reference_element);
+                let u_0 = |xs: &Vector<f64>| {
+                    U::from_components(vec![xs.iter().map(|x| x.sin()).collect()])
+                };
+                let storages = heat_1d(
+                    &u_0,
+                    &grid,
+                    reference_element,
+                    &operators,
+                    1.0,
+                    DiffusionFlux::LDG { tau: 0. },
+                    t,
+                    &mut ClassicalRK4 {},
+                );
+                error_norms(&grid, &storages, &operators, 0, |x, t| (-t).exp() * x.sin(), t)
+            },
+        );
+
+        // LDG with alternating fluxes converges at the optimal order N + 1 in L2.
+        for &n in [1, 2].iter() {
+            let finest = table.order(n)[2];
+            assert!(finest.l2_rate.expect("not the coarsest run") > n as f64 + 0.8);
+            assert!(finest.h1_seminorm_rate.expect("not the coarsest run") > n as f64 - 0.2);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+extern crate rulinalg;
+
+use self::rulinalg::vector::Vector;
+use galerkin_1d::galerkin::flux_divergence;
+use galerkin_1d::galerkin::GalerkinScheme;
+use galerkin_1d::grid::{cfl_time_step, ElementStorage, FaceType, Grid, ReferenceElement};
+use galerkin_1d::grid::SpatialFlux;
+use galerkin_1d::operators::Operators;
+use galerkin_1d::time_stepping::SpatialOperator;
+use galerkin_1d::unknowns::{communicate, Components};
+use std::cell::Cell;
+
+// Numerical fluxes for the local discontinuous Galerkin discretization of (nu u_x)_x, which
+// introduces the auxiliary variable q = u_x. With {.} the average and [[v]] = n (v- - v+)
+// the jump across a face, the fluxes are
+//
+//     u* = {u} + beta [[u]],    q* = {q} - beta [[q]] - tau [[u]].
+//
+// Boundary faces always use beta = 0 and take q+ = q-, so that a boundary condition which
+// mirrors u about its boundary value, u+ = 2 g - u-, imposes u = g.
+#[derive(Clone, Copy, Debug)]
+pub enum DiffusionFlux {
+    // Alternating fluxes, beta = 1/2: u* is taken from the left of each face and q* from the
+    // right. Stable for any tau >= 0.
+    LDG { tau: f64 },
+
+    // Central fluxes, beta = 0. A penalty tau of the order of 1/h is needed for accuracy.
+    Central { tau: f64 },
+}
+
+impl DiffusionFlux {
+    fn beta<GS: GalerkinScheme>(&self, face_type: &FaceType<GS>) -> f64 {
+        match (*self, face_type) {
+            (_, FaceType::Boundary(_, _)) => 0.,
+            (DiffusionFlux::LDG { .. }, _) => 0.5,
+            (DiffusionFlux::Central { .. }, _) => 0.,
+        }
+    }
+
+    fn tau(&self) -> f64 {
+        match *self {
+            DiffusionFlux::LDG { tau } => tau,
+            DiffusionFlux::Central { tau } => tau,
+        }
+    }
+}
+
+// The LDG approximation of q = u_x on every element. The face values of u must already
+// have been communicated.
+pub fn ldg_gradient<GS, U>(
+    grid: &Grid<GS>,
+    storages: &Vec<ElementStorage<U, GS::F>>,
+    operators: &Operators,
+    flux: DiffusionFlux,
+) -> Vec<Vector<f64>>
+where
+    GS: GalerkinScheme<U = U>,
+    U: Components<Unit = f64>,
+{
+    grid.elements
+        .iter()
+        .map(|elt| {
+            let storage = &storages[elt.index as usize];
+
+            // n (u- - u*) on each face
+            let jump = |u_minus: f64, u_plus: f64, n: f64, face_type| {
+                let u_star =
+                    (u_minus + u_plus) / 2. + flux.beta(face_type) * n * (u_minus - u_plus);
+                n * (u_minus - u_star)
+            };
+            let du = vector![
+                jump(
+                    storage.u_left_minus.get(),
+                    storage.u_left_plus.get(),
+                    elt.left_outward_normal,
+                    &elt.left_face.face_type,
+                ),
+                jump(
+                    storage.u_right_minus.get(),
+                    storage.u_right_plus.get(),
+                    elt.right_outward_normal,
+                    &elt.right_face.face_type,
+                )
+            ];
+            let u = storage.u_k.components()[0];
+            -flux_divergence(elt, storage, operators, u, &du)
+        })
+        .collect()
+}
+
+// The LDG approximation of (nu q)_x on every element, given nu * q. The face values of u
+// must already have been communicated; those of q are communicated here.
+pub fn ldg_divergence<GS, U>(
+    t: f64,
+    grid: &Grid<GS>,
+    storages: &Vec<ElementStorage<U, GS::F>>,
+    nu_q: Vec<Vector<f64>>,
+    nu: f64,
+    operators: &Operators,
+    flux: DiffusionFlux,
+) -> Vec<Vector<f64>>
+where
+    GS: GalerkinScheme<U = U>,
+    U: Components<Unit = f64>,
+{
+    let q_storages: Vec<ElementStorage<U, GS::F>> = storages
+        .iter()
+        .zip(nu_q.into_iter())
+        .map(|(storage, nu_q)| auxiliary_storage(storage, nu_q))
+        .collect();
+    communicate(t, grid, &q_storages);
+
+    grid.elements
+        .iter()
+        .map(|elt| {
+            let storage = &storages[elt.index as usize];
+            let q_storage = &q_storages[elt.index as usize];
+
+            // n (nu q- - nu q*) on each face
+            let jump = |u: (f64, f64), q: (f64, f64), n: f64, face_type| {
+                let (u_minus, u_plus) = u;
+                let (q_minus, q_plus) = match face_type {
+                    &FaceType::Boundary(_, _) => (q.0, q.0),
+                    &FaceType::Interior(_) => q,
+                };
+                let q_star = (q_minus + q_plus) / 2.
+                    - flux.beta(face_type) * n * (q_minus - q_plus)
+                    - nu * flux.tau() * n * (u_minus - u_plus);
+                n * (q_minus - q_star)
+            };
+            let dq = vector![
+                jump(
+                    (storage.u_left_minus.get(), storage.u_left_plus.get()),
+                    (q_storage.u_left_minus.get(), q_storage.u_left_plus.get()),
+                    elt.left_outward_normal,
+                    &elt.left_face.face_type,
+                ),
+                jump(
+                    (storage.u_right_minus.get(), storage.u_right_plus.get()),
+                    (q_storage.u_right_minus.get(), q_storage.u_right_plus.get()),
+                    elt.right_outward_normal,
+                    &elt.right_face.face_type,
+                )
+            ];
+            let nu_q = q_storage.u_k.components()[0];
+            -flux_divergence(elt, q_storage, operators, nu_q, &dq)
+        })
+        .collect()
+}
+
+// Storage for the auxiliary variable, sharing the geometry of `storage`, so that it can be
+// passed through `communicate`.
+fn auxiliary_storage<U, F>(storage: &ElementStorage<U, F>, q: Vector<f64>) -> ElementStorage<U, F>
+where
+    U: Components,
+    F: SpatialFlux,
+{
+    ElementStorage {
+        r_x: storage.r_x.clone(),
+        r_x_at_faces: storage.r_x_at_faces.clone(),
+        u_k: U::from_components(vec![q]),
+        u_left_minus: Cell::new(U::zero()),
+        u_left_plus: Cell::new(U::zero()),
+        u_right_minus: Cell::new(U::zero()),
+        u_right_plus: Cell::new(U::zero()),
+        f_left_minus: Cell::new(storage.f_left_minus.get()),
+        f_left_plus: Cell::new(storage.f_left_plus.get()),
+        f_right_minus: Cell::new(storage.f_right_minus.get()),
+        f_right_plus: Cell::new(storage.f_right_plus.get()),
+    }
+}
+
+// The spatial operator L(u) = hyperbolic(u) + (nu u_x)_x for a scalar unknown with constant
+// diffusivity nu, where the second-order term is discretized by LDG.
+pub struct Diffusion<'a, RHS> {
+    pub hyperbolic: RHS,
+    pub nu: f64,
+    pub flux: DiffusionFlux,
+    pub operators: &'a Operators,
+}
+
+impl<'a, GS, U, RHS> SpatialOperator<GS> for Diffusion<'a, RHS>
+where
+    GS: GalerkinScheme<U = U>,
+    U: Components<Unit = f64>,
+    RHS: SpatialOperator<GS>,
+{
+    fn evaluate(
+        &self,
+        t: f64,
+        grid: &Grid<GS>,
+        storages: &Vec<ElementStorage<U, GS::F>>,
+    ) -> Vec<U> {
+        // This also communicates the face values of u.
+        let hyperbolic = self.hyperbolic.evaluate(t, grid, storages);
+
+        let nu_q = ldg_gradient(grid, storages, self.operators, self.flux)
+            .into_iter()
+            .map(|q| q * self.nu)
+            .collect();
+        let diffusion = ldg_divergence(
+            t,
+            grid,
+            storages,
+            nu_q,
+            self.nu,
+            self.operators,
+            self.flux,
+        );
+
+        hyperbolic
+            .into_iter()
+            .zip(diffusion.into_iter())
+            .map(|(hyperbolic, diffusion)| hyperbolic + U::from_components(vec![diffusion]))
+            .collect()
+    }
+}
+
+// The largest stable time step for explicit integration of the diffusive terms, which
+// scales with the square of the smallest node spacing.
+pub fn diffusive_time_step<GS: GalerkinScheme>(
+    grid: &Grid<GS>,
+    reference_element: &ReferenceElement,
+    nu: f64,
+    cfl: f64,
+) -> f64 {
+    let dx_min = cfl_time_step(grid, reference_element, 1., 1.);
+    cfl * dx_min * dx_min / nu
+}
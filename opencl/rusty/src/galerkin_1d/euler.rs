@@ -0,0 +1,849 @@
+extern crate rulinalg;
+
+use self::rulinalg::vector::Vector;
+use functions::filter::Filter;
+use galerkin_1d::flux::FluxEnum;
+use galerkin_1d::flux::FluxScheme;
+use galerkin_1d::flux::NumericalFlux;
+use galerkin_1d::flux::Side;
+use galerkin_1d::flux::{ConservationFlux, ConservationLaw, RiemannSolver};
+use galerkin_1d::galerkin::compute_flux;
+use galerkin_1d::galerkin::flux_divergence;
+use galerkin_1d::galerkin::Formulation;
+use galerkin_1d::galerkin::GalerkinScheme;
+use galerkin_1d::grid;
+use galerkin_1d::grid::FaceType;
+use galerkin_1d::grid::{cfl_time_step, generate_grid, ReferenceElement};
+use galerkin_1d::limiters::SlopeLimiter;
+use galerkin_1d::operators::{assemble_operators, Operators};
+use galerkin_1d::time_stepping::{integrate_adaptive, SSPRK3, TimeStepper};
+use galerkin_1d::unknowns::{apply_filter, initialize_storage, Components, Unknown};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// The ratio of specific heats for a diatomic ideal gas.
+const GAMMA: f64 = 1.4;
+
+// The conserved variables of the 1D Euler equations: density, momentum and total energy.
+#[derive(Debug, Clone)]
+pub struct EulerState {
+    pub rho: Vector<f64>,
+    pub rho_u: Vector<f64>,
+    pub energy: Vector<f64>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct EulerUnit {
+    pub rho: f64,
+    pub rho_u: f64,
+    pub energy: f64,
+}
+
+impl EulerUnit {
+    // Build the conserved state from density, velocity and pressure.
+    pub fn from_primitive(rho: f64, u: f64, p: f64) -> EulerUnit {
+        EulerUnit {
+            rho,
+            rho_u: rho * u,
+            energy: p / (GAMMA - 1.) + 0.5 * rho * u * u,
+        }
+    }
+
+    fn velocity(&self) -> f64 {
+        self.rho_u / self.rho
+    }
+
+    fn pressure(&self) -> f64 {
+        (GAMMA - 1.) * (self.energy - 0.5 * self.rho_u * self.rho_u / self.rho)
+    }
+
+    fn sound_speed(&self) -> f64 {
+        (GAMMA * self.pressure() / self.rho).sqrt()
+    }
+
+    // The physical flux (rho u, rho u^2 + p, (E + p) u).
+    fn flux(&self) -> EulerUnit {
+        let u = self.velocity();
+        let p = self.pressure();
+        EulerUnit {
+            rho: self.rho_u,
+            rho_u: self.rho_u * u + p,
+            energy: (self.energy + p) * u,
+        }
+    }
+}
+
+impl Add for EulerUnit {
+    type Output = EulerUnit;
+
+    fn add(self, other: EulerUnit) -> EulerUnit {
+        EulerUnit {
+            rho: self.rho + other.rho,
+            rho_u: self.rho_u + other.rho_u,
+            energy: self.energy + other.energy,
+        }
+    }
+}
+
+impl Sub for EulerUnit {
+    type Output = EulerUnit;
+
+    fn sub(self, other: EulerUnit) -> EulerUnit {
+        EulerUnit {
+            rho: self.rho - other.rho,
+            rho_u: self.rho_u - other.rho_u,
+            energy: self.energy - other.energy,
+        }
+    }
+}
+
+impl Mul<f64> for EulerUnit {
+    type Output = EulerUnit;
+
+    fn mul(self, other: f64) -> EulerUnit {
+        EulerUnit {
+            rho: self.rho * other,
+            rho_u: self.rho_u * other,
+            energy: self.energy * other,
+        }
+    }
+}
+
+impl Div<f64> for EulerUnit {
+    type Output = EulerUnit;
+
+    fn div(self, other: f64) -> EulerUnit {
+        EulerUnit {
+            rho: self.rho / other,
+            rho_u: self.rho_u / other,
+            energy: self.energy / other,
+        }
+    }
+}
+
+impl Neg for EulerUnit {
+    type Output = EulerUnit;
+
+    fn neg(self) -> EulerUnit {
+        EulerUnit {
+            rho: -self.rho,
+            rho_u: -self.rho_u,
+            energy: -self.energy,
+        }
+    }
+}
+
+impl Add for EulerState {
+    type Output = EulerState;
+
+    fn add(self, other: EulerState) -> EulerState {
+        EulerState {
+            rho: self.rho + other.rho,
+            rho_u: self.rho_u + other.rho_u,
+            energy: self.energy + other.energy,
+        }
+    }
+}
+
+impl Mul<f64> for EulerState {
+    type Output = EulerState;
+
+    fn mul(self, other: f64) -> EulerState {
+        EulerState {
+            rho: self.rho * other,
+            rho_u: self.rho_u * other,
+            energy: self.energy * other,
+        }
+    }
+}
+
+impl Unknown for EulerState {
+    type Unit = EulerUnit;
+
+    fn first(&self) -> Self::Unit {
+        EulerUnit {
+            rho: self.rho[0],
+            rho_u: self.rho_u[0],
+            energy: self.energy[0],
+        }
+    }
+
+    fn last(&self) -> Self::Unit {
+        let n = self.rho.size() - 1;
+        EulerUnit {
+            rho: self.rho[n],
+            rho_u: self.rho_u[n],
+            energy: self.energy[n],
+        }
+    }
+
+    fn zero() -> EulerUnit {
+        EulerUnit {
+            rho: 0.,
+            rho_u: 0.,
+            energy: 0.,
+        }
+    }
+}
+
+// The conserved variables are limited independently of one another.
+impl Components for EulerState {
+    fn components(&self) -> Vec<&Vector<f64>> {
+        vec![&self.rho, &self.rho_u, &self.energy]
+    }
+
+    fn from_components(mut components: Vec<Vector<f64>>) -> EulerState {
+        let energy = components.remove(2);
+        let rho_u = components.remove(1);
+        let rho = components.remove(0);
+        EulerState { rho, rho_u, energy }
+    }
+}
+
+type Grid = grid::Grid<Euler>;
+
+type Element = grid::Element<Euler>;
+
+type EulerStorage = grid::ElementStorage<EulerState, ()>;
+
+// The Euler equations as a conservation law, for use with the generic Riemann solvers.
+#[derive(Clone, Copy, Debug)]
+pub struct EulerLaw {}
+
+impl ConservationLaw<EulerState, ()> for EulerLaw {
+    fn flux(&self, side: &Side<EulerState, ()>) -> EulerUnit {
+        side.u.flux()
+    }
+
+    fn wave_speeds(&self, side: &Side<EulerState, ()>) -> (f64, f64) {
+        let (u, c) = (side.u.velocity(), side.u.sound_speed());
+        (u - c, u + c)
+    }
+
+    // Roe's linearization about the density-weighted average state, following Toro, with
+    // Harten's entropy fix to keep rarefactions from turning into expansion shocks.
+    fn roe_dissipation(
+        &self,
+        left: &Side<EulerState, ()>,
+        right: &Side<EulerState, ()>,
+    ) -> EulerUnit {
+        let (q_l, q_r) = (left.u, right.u);
+        let (w_l, w_r) = (q_l.rho.sqrt(), q_r.rho.sqrt());
+        let enthalpy = |q: EulerUnit| (q.energy + q.pressure()) / q.rho;
+        let u = (w_l * q_l.velocity() + w_r * q_r.velocity()) / (w_l + w_r);
+        let h = (w_l * enthalpy(q_l) + w_r * enthalpy(q_r)) / (w_l + w_r);
+        let c = ((GAMMA - 1.) * (h - 0.5 * u * u)).sqrt();
+
+        // The wave strengths.
+        let d = q_r - q_l;
+        let alpha_2 = (GAMMA - 1.) / (c * c) * (d.rho * (h - u * u) + u * d.rho_u - d.energy);
+        let alpha_1 = (d.rho * (u + c) - d.rho_u - c * alpha_2) / (2. * c);
+        let alpha_3 = d.rho - alpha_1 - alpha_2;
+
+        let delta = 0.1 * c;
+        let entropy_fix = |lambda: f64| {
+            if lambda.abs() < delta {
+                (lambda * lambda + delta * delta) / (2. * delta)
+            } else {
+                lambda.abs()
+            }
+        };
+
+        let k_1 = EulerUnit {
+            rho: 1.,
+            rho_u: u - c,
+            energy: h - u * c,
+        };
+        let k_2 = EulerUnit {
+            rho: 1.,
+            rho_u: u,
+            energy: 0.5 * u * u,
+        };
+        let k_3 = EulerUnit {
+            rho: 1.,
+            rho_u: u + c,
+            energy: h + u * c,
+        };
+        k_1 * (entropy_fix(u - c) * alpha_1)
+            + k_2 * (entropy_fix(u) * alpha_2)
+            + k_3 * (entropy_fix(u + c) * alpha_3)
+    }
+}
+
+// Approximate Riemann solvers for the Euler equations. Each returns the strong-form face
+// term n * (f(u-) - f*), where f* is the numerical flux.
+#[derive(Clone, Copy, Debug)]
+pub enum EulerFlux {
+    // The local Lax-Friedrichs flux, with dissipation set by the fastest wave at the face.
+    Rusanov,
+
+    // The two-wave solver of Harten, Lax and van Leer, with the wave speed estimates of Davis.
+    HLL,
+
+    // HLL with the contact wave restored, after Toro, Spruce and Speares.
+    HLLC,
+
+    // Roe's linearized Riemann solver.
+    Roe,
+}
+
+impl EulerFlux {
+    // The flux across a face with state `left` on its left and `right` on its right.
+    fn interface_flux(&self, left: EulerUnit, right: EulerUnit) -> EulerUnit {
+        let generic = |solver| {
+            let flux = ConservationFlux {
+                law: EulerLaw {},
+                solver,
+            };
+            let left: Side<EulerState, ()> = Side { u: left, f: () };
+            let right: Side<EulerState, ()> = Side { u: right, f: () };
+            flux.interface_flux(&left, &right)
+        };
+        let (u_l, u_r) = (left.velocity(), right.velocity());
+        let (c_l, c_r) = (left.sound_speed(), right.sound_speed());
+        let (f_l, f_r) = (left.flux(), right.flux());
+        match *self {
+            EulerFlux::Rusanov => generic(RiemannSolver::Rusanov),
+            EulerFlux::HLL => generic(RiemannSolver::HLL),
+            EulerFlux::Roe => generic(RiemannSolver::Roe),
+            EulerFlux::HLLC => {
+                let s_l = (u_l - c_l).min(u_r - c_r);
+                let s_r = (u_l + c_l).max(u_r + c_r);
+                let (p_l, p_r) = (left.pressure(), right.pressure());
+                let s_star = (p_r - p_l + left.rho * u_l * (s_l - u_l)
+                    - right.rho * u_r * (s_r - u_r))
+                    / (left.rho * (s_l - u_l) - right.rho * (s_r - u_r));
+                let star_state = |q: EulerUnit, u: f64, p: f64, s: f64| {
+                    let factor = q.rho * (s - u) / (s - s_star);
+                    EulerUnit {
+                        rho: factor,
+                        rho_u: factor * s_star,
+                        energy: factor
+                            * (q.energy / q.rho + (s_star - u) * (s_star + p / (q.rho * (s - u)))),
+                    }
+                };
+                if s_l >= 0. {
+                    f_l
+                } else if s_star >= 0. {
+                    f_l + (star_state(left, u_l, p_l, s_l) - left) * s_l
+                } else if s_r > 0. {
+                    f_r + (star_state(right, u_r, p_r, s_r) - right) * s_r
+                } else {
+                    f_r
+                }
+            }
+        }
+    }
+}
+
+impl NumericalFlux<EulerState, ()> for EulerFlux {
+    fn flux(
+        &self,
+        minus: Side<EulerState, ()>,
+        plus: Side<EulerState, ()>,
+        outward_normal: f64,
+    ) -> EulerUnit {
+        let f_star = if outward_normal > 0. {
+            self.interface_flux(minus.u, plus.u)
+        } else {
+            self.interface_flux(plus.u, minus.u)
+        };
+        (minus.u.flux() - f_star) * outward_normal
+    }
+}
+
+pub struct EulerFluxScheme {}
+
+impl FluxScheme<EulerState, ()> for EulerFluxScheme {
+    type Left = EulerFlux;
+    type Right = EulerFlux;
+    type Interior = EulerFlux;
+}
+
+pub struct Euler {}
+
+impl GalerkinScheme for Euler {
+    type U = EulerState;
+    type F = ();
+    type FS = EulerFluxScheme;
+
+    const FORMULATION: Formulation = Formulation::Strong;
+}
+
+// A solid wall: the exterior state mirrors the interior one with the velocity reversed.
+pub fn reflective_boundary() -> FaceType<Euler> {
+    FaceType::Boundary(
+        Box::new(move |_, other_side: EulerUnit| EulerUnit {
+            rho: other_side.rho,
+            rho_u: -other_side.rho_u,
+            energy: other_side.energy,
+        }),
+        (),
+    )
+}
+
+// An open boundary through which waves leave the domain without reflection.
+pub fn transmissive_boundary() -> FaceType<Euler> {
+    FaceType::Boundary(Box::new(move |_, other_side: EulerUnit| other_side), ())
+}
+
+pub fn euler_1d<Fx, TS>(
+    u_0: Fx,
+    grid: &Grid,
+    reference_element: &ReferenceElement,
+    operators: &Operators,
+    final_time: f64,
+    stepper: &mut TS,
+    limiter: Option<SlopeLimiter>,
+    filter: Option<&Filter>,
+) -> Vec<EulerStorage>
+where
+    Fx: Fn(&Vector<f64>) -> EulerState,
+    TS: TimeStepper<EulerState>,
+{
+    let mut storage: Vec<EulerStorage> =
+        initialize_storage(u_0, reference_element.n_p, grid, operators);
+
+    // Shocks and rarefactions change the wave speeds as the solution evolves, so the step
+    // is chosen afresh each time from the fastest wave, |u| + c.
+    let cfl = 0.2;
+
+    integrate_adaptive(
+        final_time,
+        grid,
+        &mut storage,
+        stepper,
+        |elt, elt_storage, _| euler_rhs_1d(elt, elt_storage, operators),
+        |storages| cfl_time_step(grid, reference_element, max_speed(storages), cfl),
+        |grid, storages| {
+            if let Some(limiter) = limiter {
+                limiter.limit(grid, storages, operators);
+            }
+        },
+        |epoch, _, storages| {
+            if let Some(filter) = filter {
+                if filter.is_due(epoch + 1) {
+                    apply_filter(filter.matrix(), storages);
+                }
+            }
+        },
+    );
+
+    storage
+}
+
+// The fastest signal speed anywhere in the solution.
+fn max_speed(storages: &Vec<EulerStorage>) -> f64 {
+    storages
+        .iter()
+        .flat_map(|storage| {
+            let u_k = &storage.u_k;
+            (0..u_k.rho.size())
+                .map(|i| {
+                    let q = EulerUnit {
+                        rho: u_k.rho[i],
+                        rho_u: u_k.rho_u[i],
+                        energy: u_k.energy[i],
+                    };
+                    q.velocity().abs() + q.sound_speed()
+                })
+                .collect::<Vec<f64>>()
+        })
+        .fold(0., f64::max)
+}
+
+fn euler_rhs_1d(elt: &Element, elt_storage: &EulerStorage, operators: &Operators) -> EulerState {
+    let (flux_left, flux_right) = compute_flux(elt, elt_storage);
+
+    let q = &elt_storage.u_k;
+    let u = q.rho_u.elediv(&q.rho);
+    let p = (&q.energy - &q.rho_u.elemul(&u) * 0.5) * (GAMMA - 1.);
+    let f_rho = q.rho_u.clone();
+    let f_rho_u = q.rho_u.elemul(&u) + &p;
+    let f_energy = (&q.energy + &p).elemul(&u);
+
+    let rho = flux_divergence(
+        elt,
+        elt_storage,
+        operators,
+        &f_rho,
+        &vector![flux_left.rho, flux_right.rho],
+    );
+    let rho_u = flux_divergence(
+        elt,
+        elt_storage,
+        operators,
+        &f_rho_u,
+        &vector![flux_left.rho_u, flux_right.rho_u],
+    );
+    let energy = flux_divergence(
+        elt,
+        elt_storage,
+        operators,
+        &f_energy,
+        &vector![flux_left.energy, flux_right.energy],
+    );
+
+    EulerState { rho, rho_u, energy }
+}
+
+fn from_primitive<Fp>(xs: &Vector<f64>, primitive: Fp) -> EulerState
+where
+    Fp: Fn(f64) -> (f64, f64, f64),
+{
+    let units: Vec<EulerUnit> = xs
+        .iter()
+        .map(|&x| {
+            let (rho, u, p) = primitive(x);
+            EulerUnit::from_primitive(rho, u, p)
+        })
+        .collect();
+    EulerState {
+        rho: units.iter().map(|q| q.rho).collect(),
+        rho_u: units.iter().map(|q| q.rho_u).collect(),
+        energy: units.iter().map(|q| q.energy).collect(),
+    }
+}
+
+// Sod's shock tube on [0, 1], with the diaphragm at x = 0.5. Usually run to t = 0.2.
+pub fn sod_shock_tube(xs: &Vector<f64>) -> EulerState {
+    from_primitive(xs, |x| {
+        if x < 0.5 {
+            (1.0, 0.0, 1.0)
+        } else {
+            (0.125, 0.0, 0.1)
+        }
+    })
+}
+
+// The Shu-Osher problem on [-5, 5]: a Mach 3 shock running into a sinusoidal density field.
+// Usually run to t = 1.8.
+pub fn shu_osher(xs: &Vector<f64>) -> EulerState {
+    from_primitive(xs, |x| {
+        if x < -4. {
+            (3.857143, 2.629369, 10.33333)
+        } else {
+            (1. + 0.2 * (5. * x).sin(), 0.0, 1.0)
+        }
+    })
+}
+
+pub fn euler_1d_example() {
+    let n_p = 2;
+    let reference_element = ReferenceElement::legendre(n_p);
+    let left_boundary_face = grid::Face {
+        face_type: transmissive_boundary(),
+        flux: FluxEnum::Left(EulerFlux::HLLC),
+    };
+    let right_boundary_face = grid::Face {
+        face_type: transmissive_boundary(),
+        flux: FluxEnum::Right(EulerFlux::HLLC),
+    };
+    let grid: Grid = generate_grid(
+        0.0,
+        1.0,
+        100,
+        &reference_element,
+        left_boundary_face,
+        right_boundary_face,
+        EulerFlux::HLLC,
+        |_| (),
+    );
+    let operators = assemble_operators::<EulerState>(&reference_element);
+
+    let storages = euler_1d(
+        &sod_shock_tube,
+        &grid,
+        &reference_element,
+        &operators,
+        0.2,
+        &mut SSPRK3 {},
+        Some(SlopeLimiter::Minmod),
+        None,
+    );
+
+    for storage in storages.iter() {
+        println!("{:?}", &storage.u_k.rho);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::{
+        euler_1d, from_primitive, reflective_boundary, shu_osher, sod_shock_tube,
+        transmissive_boundary, Euler, EulerFlux, EulerState, EulerStorage, EulerUnit, Grid, GAMMA,
+    };
+    use galerkin_1d::flux::FluxEnum;
+    use galerkin_1d::grid::{
+        generate_grid, generate_periodic_grid, Face, FaceType, ReferenceElement,
+    };
+    use galerkin_1d::limiters::SlopeLimiter;
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::time_stepping::SSPRK3;
+    use galerkin_1d::unknowns::initialize_storage;
+    use rulinalg::vector::Vector;
+    use std::f64::consts;
+
+    const FLUXES: [EulerFlux; 4] = [
+        EulerFlux::Rusanov,
+        EulerFlux::HLL,
+        EulerFlux::HLLC,
+        EulerFlux::Roe,
+    ];
+
+    // A grid on [x_min, x_max] with the given boundary condition at both ends.
+    fn grid<Fc>(
+        x_min: f64,
+        x_max: f64,
+        n_k: i32,
+        reference_element: &ReferenceElement,
+        flux: EulerFlux,
+        boundary: Fc,
+    ) -> Grid
+    where
+        Fc: Fn() -> FaceType<Euler>,
+    {
+        generate_grid(
+            x_min,
+            x_max,
+            n_k,
+            reference_element,
+            Face {
+                face_type: boundary(),
+                flux: FluxEnum::Left(flux),
+            },
+            Face {
+                face_type: boundary(),
+                flux: FluxEnum::Right(flux),
+            },
+            flux,
+            |_| (),
+        )
+    }
+
+    // The integrals of density and total energy over the whole grid.
+    fn totals(grid: &Grid, storages: &Vec<EulerStorage>, weights: &Vector<f64>) -> (f64, f64) {
+        let mut mass = 0.;
+        let mut energy = 0.;
+        for (elt, storage) in grid.elements.iter().zip(storages.iter()) {
+            let jacobian = (elt.x_right - elt.x_left) / 2.;
+            mass += jacobian * weights.dot(&storage.u_k.rho);
+            energy += jacobian * weights.dot(&storage.u_k.energy);
+        }
+        (mass, energy)
+    }
+
+    #[test]
+    fn test_fluxes_are_consistent() {
+        let q = EulerUnit::from_primitive(1.3, 0.7, 2.1);
+        let f = q.flux();
+        for flux in FLUXES.iter() {
+            let f_star = flux.interface_flux(q, q);
+            assert!((f_star.rho - f.rho).abs() < 1e-12);
+            assert!((f_star.rho_u - f.rho_u).abs() < 1e-12);
+            assert!((f_star.energy - f.energy).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_hllc_resolves_stationary_contact() {
+        // A contact discontinuity at rest should carry no mass flux.
+        let left = EulerUnit::from_primitive(1.0, 0.0, 1.0);
+        let right = EulerUnit::from_primitive(0.125, 0.0, 1.0);
+        let f_star = EulerFlux::HLLC.interface_flux(left, right);
+        assert!(f_star.rho.abs() < 1e-12);
+        assert!((f_star.rho_u - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_density_wave() {
+        // A density perturbation advected at constant velocity and pressure.
+        let density_wave = |xs: &Vector<f64>, t: f64| -> EulerState {
+            from_primitive(xs, |x| (1. + 0.2 * (consts::PI * (x - t)).sin(), 1.0, 1.0))
+        };
+        for flux in FLUXES.iter() {
+            let n_p = 4;
+            let reference_element = ReferenceElement::legendre(n_p);
+            let grid: Grid =
+                generate_periodic_grid(0.0, 2.0, 20, &reference_element, *flux, |_| ());
+            let operators = assemble_operators::<EulerState>(&reference_element);
+            let storages = euler_1d(
+                &|xs: &Vector<f64>| density_wave(xs, 0.),
+                &grid,
+                &reference_element,
+                &operators,
+                0.5,
+                &mut SSPRK3 {},
+                None,
+                None,
+            );
+            for (elt, storage) in grid.elements.iter().zip(storages.iter()) {
+                let expected = density_wave(&elt.x_k, 0.5);
+                for (rho, rho_exact) in storage.u_k.rho.iter().zip(expected.rho.iter()) {
+                    assert!((rho - rho_exact).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    // The exact density of Sod's shock tube, found by solving the Riemann problem for the
+    // pressure between the two nonlinear waves. For Sod's data these are a rarefaction running
+    // left and a shock running right, with the contact between them.
+    fn sod_density(x: f64, t: f64) -> f64 {
+        let (rho_l, p_l) = (1.0, 1.0);
+        let (rho_r, p_r) = (0.125, 0.1);
+        let c_l = (GAMMA * p_l / rho_l).sqrt();
+        let c_r = (GAMMA * p_r / rho_r).sqrt();
+
+        // The velocity change across each wave for a given pressure behind it.
+        let f_l =
+            |p: f64| 2. * c_l / (GAMMA - 1.) * ((p / p_l).powf((GAMMA - 1.) / (2. * GAMMA)) - 1.);
+        let f_r = |p: f64| {
+            let a = 2. / ((GAMMA + 1.) * rho_r);
+            let b = (GAMMA - 1.) / (GAMMA + 1.) * p_r;
+            (p - p_r) * (a / (p + b)).sqrt()
+        };
+
+        // f_l + f_r increases with the pressure, so bisect for its root.
+        let (mut p_low, mut p_high) = (p_r, p_l);
+        for _ in 0..100 {
+            let p = (p_low + p_high) / 2.;
+            if f_l(p) + f_r(p) > 0. {
+                p_high = p;
+            } else {
+                p_low = p;
+            }
+        }
+        let p_star = (p_low + p_high) / 2.;
+        let u_star = (f_r(p_star) - f_l(p_star)) / 2.;
+
+        let mu = (GAMMA - 1.) / (GAMMA + 1.);
+        let c_star_l = c_l * (p_star / p_l).powf((GAMMA - 1.) / (2. * GAMMA));
+        let shock_speed =
+            c_r * ((GAMMA + 1.) / (2. * GAMMA) * p_star / p_r + (GAMMA - 1.) / (2. * GAMMA)).sqrt();
+        let s = (x - 0.5) / t;
+        if s < -c_l {
+            rho_l
+        } else if s < u_star - c_star_l {
+            rho_l * (2. / (GAMMA + 1.) - mu / c_l * s).powf(2. / (GAMMA - 1.))
+        } else if s < u_star {
+            rho_l * (p_star / p_l).powf(1. / GAMMA)
+        } else if s < shock_speed {
+            rho_r * (p_star / p_r + mu) / (mu * p_star / p_r + 1.)
+        } else {
+            rho_r
+        }
+    }
+
+    #[test]
+    fn test_limited_sod_shock_tube() {
+        let n_p = 2;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let operators = assemble_operators::<EulerState>(&reference_element);
+        let weights = &operators.mass * Vector::ones(n_p as usize + 1);
+        for flux in FLUXES.iter() {
+            let grid = grid(
+                0.0,
+                1.0,
+                100,
+                &reference_element,
+                *flux,
+                transmissive_boundary,
+            );
+            let final_time = 0.2;
+            let storages = euler_1d(
+                &sod_shock_tube,
+                &grid,
+                &reference_element,
+                &operators,
+                final_time,
+                &mut SSPRK3 {},
+                Some(SlopeLimiter::Minmod),
+                None,
+            );
+
+            // With limiting, the density stays between its initial extremes, and converges to
+            // the exact solution at first order because of the discontinuities.
+            let mut l1_error = 0.;
+            for (elt, storage) in grid.elements.iter().zip(storages.iter()) {
+                let rho = &storage.u_k.rho;
+                assert!(rho.iter().all(|&rho| rho > 0.125 - 1e-2 && rho < 1. + 1e-2));
+                let jacobian = (elt.x_right - elt.x_left) / 2.;
+                for i in 0..rho.size() {
+                    let error = (rho[i] - sod_density(elt.x_k[i], final_time)).abs();
+                    l1_error += jacobian * weights[i] * error;
+                }
+            }
+            assert!(l1_error < 1.5e-2);
+        }
+    }
+
+    #[test]
+    fn test_closed_tube_conserves_mass_and_energy() {
+        // Sod's shock tube between two walls, run long enough for the shock to reflect. No
+        // mass or energy crosses a wall, and the limiter keeps each element's means.
+        let n_p = 2;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let operators = assemble_operators::<EulerState>(&reference_element);
+        let weights = &operators.mass * Vector::ones(n_p as usize + 1);
+        for flux in FLUXES.iter() {
+            let grid = grid(0.0, 1.0, 50, &reference_element, *flux, reflective_boundary);
+            let initial: Vec<EulerStorage> =
+                initialize_storage(&sod_shock_tube, n_p, &grid, &operators);
+            let storages = euler_1d(
+                &sod_shock_tube,
+                &grid,
+                &reference_element,
+                &operators,
+                0.4,
+                &mut SSPRK3 {},
+                Some(SlopeLimiter::Minmod),
+                None,
+            );
+            let (mass_0, energy_0) = totals(&grid, &initial, &weights);
+            let (mass, energy) = totals(&grid, &storages, &weights);
+            assert!((mass - mass_0).abs() < 1e-12 * mass_0);
+            assert!((energy - energy_0).abs() < 1e-12 * energy_0);
+        }
+    }
+
+    #[test]
+    fn test_shu_osher_stays_positive() {
+        let n_p = 2;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let operators = assemble_operators::<EulerState>(&reference_element);
+        for flux in FLUXES.iter() {
+            let grid = grid(
+                -5.0,
+                5.0,
+                100,
+                &reference_element,
+                *flux,
+                transmissive_boundary,
+            );
+            let storages = euler_1d(
+                &shu_osher,
+                &grid,
+                &reference_element,
+                &operators,
+                0.3,
+                &mut SSPRK3 {},
+                Some(SlopeLimiter::Minmod),
+                None,
+            );
+            for storage in storages.iter() {
+                let u_k = &storage.u_k;
+                for i in 0..u_k.rho.size() {
+                    let q = EulerUnit {
+                        rho: u_k.rho[i],
+                        rho_u: u_k.rho_u[i],
+                        energy: u_k.energy[i],
+                    };
+                    assert!(q.rho > 0.);
+                    assert!(q.pressure() > 0.);
+                }
+            }
+        }
+    }
+}
@@ -26,6 +26,8 @@ pub enum FluxEnum<U: Unknown, F: SpatialFlux, FS: FluxScheme<U, F>> {
     Interior(FS::Interior),
 }
 
+// The Lax-Friedrichs flux for linear advection with speeds `minus.f` and `plus.f` on either
+// side of the face. alpha = 0 gives the upwind flux and alpha = 1 the central flux.
 #[derive(Clone, Copy)]
 pub struct LaxFriedrichs {
     pub alpha: f64,
@@ -40,11 +42,140 @@ where
         let f_minus = minus.u * minus.f;
         let f_plus = plus.u * plus.f;
         let avg = (f_minus + f_plus) / 2.;
-        let jump = f_minus * outward_normal - f_plus * outward_normal;
+        let c = minus.f.abs().max(plus.f.abs());
 
-        let f_numerical = avg + jump / 2.;
+        // n * f*
+        let f_numerical =
+            avg * outward_normal + (1. - self.alpha) * c / 2. * (minus.u - plus.u);
 
-        (minus.u * minus.f - f_numerical) * outward_normal
+        f_minus * outward_normal - f_numerical
+    }
+}
+
+// A hyperbolic conservation law u_t + f(u)_x = 0, described pointwise so that the fluxes
+// in `RiemannSolver` can be written once for every equation.
+pub trait ConservationLaw<U: Unknown, F: SpatialFlux>: Copy {
+    // The physical flux f(u).
+    fn flux(&self, side: &Side<U, F>) -> U::Unit;
+
+    // The slowest and fastest characteristic speeds, i.e. the extreme eigenvalues of the
+    // flux Jacobian.
+    fn wave_speeds(&self, side: &Side<U, F>) -> (f64, f64);
+
+    // The Roe dissipation |A| (u_right - u_left), where A is the Roe matrix satisfying
+    // A (u_right - u_left) = f(u_right) - f(u_left).
+    fn roe_dissipation(&self, left: &Side<U, F>, right: &Side<U, F>) -> U::Unit;
+}
+
+// Approximate Riemann solvers which work for any `ConservationLaw`.
+#[derive(Clone, Copy, Debug)]
+pub enum RiemannSolver {
+    // The flux from whichever side the waves come from, as judged by the mean wave speed.
+    // This is exact for linear advection, but has no dissipation at sonic points.
+    Upwind,
+
+    // The local Lax-Friedrichs flux, with dissipation set by the fastest wave on either
+    // side of the face.
+    Rusanov,
+
+    // Roe's flux-difference splitting, which upwinds each characteristic field of the
+    // linearized problem.
+    Roe,
+
+    // The two-wave solver of Harten, Lax and van Leer, with the wave speed estimates of Davis.
+    HLL,
+}
+
+// A `RiemannSolver` applied to the conservation law `law`. Returns the strong-form face
+// term n * (f(u-) - f*), where f* is the numerical flux.
+#[derive(Clone, Copy, Debug)]
+pub struct ConservationFlux<L> {
+    pub law: L,
+    pub solver: RiemannSolver,
+}
+
+impl<L> ConservationFlux<L> {
+    // The flux f* across a face with `left` on its left and `right` on its right.
+    pub fn interface_flux<U, F>(&self, left: &Side<U, F>, right: &Side<U, F>) -> U::Unit
+    where
+        U: Unknown,
+        F: SpatialFlux,
+        L: ConservationLaw<U, F>,
+    {
+        let (f_l, f_r) = (self.law.flux(left), self.law.flux(right));
+        let (min_l, max_l) = self.law.wave_speeds(left);
+        let (min_r, max_r) = self.law.wave_speeds(right);
+        match self.solver {
+            RiemannSolver::Upwind => {
+                if min_l + max_l + min_r + max_r >= 0. {
+                    f_l
+                } else {
+                    f_r
+                }
+            }
+            RiemannSolver::Rusanov => {
+                let c = min_l
+                    .abs()
+                    .max(max_l.abs())
+                    .max(min_r.abs())
+                    .max(max_r.abs());
+                (f_l + f_r) * 0.5 + (left.u + -right.u) * (0.5 * c)
+            }
+            RiemannSolver::Roe => {
+                (f_l + f_r) * 0.5 + -self.law.roe_dissipation(left, right) * 0.5
+            }
+            RiemannSolver::HLL => {
+                let s_l = min_l.min(min_r);
+                let s_r = max_l.max(max_r);
+                if s_l >= 0. {
+                    f_l
+                } else if s_r <= 0. {
+                    f_r
+                } else {
+                    (f_l * s_r + -f_r * s_l + (right.u + -left.u) * (s_l * s_r)) / (s_r - s_l)
+                }
+            }
+        }
+    }
+}
+
+impl<U, F, L> NumericalFlux<U, F> for ConservationFlux<L>
+where
+    U: Unknown,
+    F: SpatialFlux,
+    L: ConservationLaw<U, F>,
+{
+    fn flux(&self, minus: Side<U, F>, plus: Side<U, F>, outward_normal: f64) -> U::Unit {
+        let f_star = if outward_normal > 0. {
+            self.interface_flux(&minus, &plus)
+        } else {
+            self.interface_flux(&plus, &minus)
+        };
+        (self.law.flux(&minus) + -f_star) * outward_normal
+    }
+}
+
+// Linear advection f(u) = a u, where the speed a is the spatial flux parameter.
+#[derive(Clone, Copy, Debug)]
+pub struct LinearAdvection {}
+
+impl<U, F> ConservationLaw<U, F> for LinearAdvection
+where
+    U: Unknown<Unit = f64>,
+    F: SpatialFlux<Unit = f64>,
+{
+    fn flux(&self, side: &Side<U, F>) -> f64 {
+        side.f * side.u
+    }
+
+    fn wave_speeds(&self, side: &Side<U, F>) -> (f64, f64) {
+        (side.f, side.f)
+    }
+
+    // Where the speed jumps across the face there is no exact Roe matrix; the mean speed
+    // is used instead.
+    fn roe_dissipation(&self, left: &Side<U, F>, right: &Side<U, F>) -> f64 {
+        ((left.f + right.f) / 2.).abs() * (right.u - left.u)
     }
 }
 
@@ -60,3 +191,53 @@ where
         U::zero()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ConservationFlux, LaxFriedrichs, LinearAdvection, NumericalFlux, RiemannSolver, Side,
+    };
+    use galerkin_1d::advec::U;
+
+    fn side(u: f64, a: f64) -> Side<U, f64> {
+        Side { u, f: a }
+    }
+
+    #[test]
+    fn test_linear_advection_fluxes_are_upwind() {
+        // For linear advection every solver reduces to the upwind flux.
+        let solvers = [
+            RiemannSolver::Upwind,
+            RiemannSolver::Rusanov,
+            RiemannSolver::Roe,
+            RiemannSolver::HLL,
+        ];
+        for &solver in solvers.iter() {
+            let flux = ConservationFlux {
+                law: LinearAdvection {},
+                solver,
+            };
+            for &a in [2., -2.].iter() {
+                let (u_l, u_r) = (1.5, -0.5);
+                let expected = if a > 0. { a * u_l } else { a * u_r };
+                let f_star = flux.interface_flux(&side(u_l, a), &side(u_r, a));
+                assert!((f_star - expected).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lax_friedrichs_alpha() {
+        let (u_minus, u_plus, a) = (1.5, -0.5, 2.);
+
+        // alpha = 0 is upwind: on a right face with a > 0, f* = a u-, so no jump term.
+        let upwind = LaxFriedrichs { alpha: 0. };
+        let du = NumericalFlux::<U, f64>::flux(&upwind, side(u_minus, a), side(u_plus, a), 1.);
+        assert!(du.abs() < 1e-12);
+
+        // alpha = 1 is central: f* is the average of the two sides.
+        let central = LaxFriedrichs { alpha: 1. };
+        let du = NumericalFlux::<U, f64>::flux(&central, side(u_minus, a), side(u_plus, a), 1.);
+        assert!((du - a * (u_minus - u_plus) / 2.).abs() < 1e-12);
+    }
+}
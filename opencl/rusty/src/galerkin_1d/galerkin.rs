@@ -7,7 +7,9 @@ use galerkin_1d::flux::Side;
 use galerkin_1d::grid::Element;
 use galerkin_1d::grid::ElementStorage;
 use galerkin_1d::grid::SpatialFlux;
+use galerkin_1d::operators::Operators;
 use galerkin_1d::unknowns::Unknown;
+use rulinalg::vector::Vector;
 
 pub trait GalerkinScheme {
     type U: Unknown;
@@ -15,6 +17,12 @@ pub trait GalerkinScheme {
     type FS: FluxScheme<Self::U, Self::F>;
 
     const FORMULATION: Formulation;
+
+    // The source term S(x, t, u) on the right-hand side of u_t + f(u)_x = S, evaluated at the
+    // nodes `xs` of an element. Schemes without a source keep the default.
+    fn source(_xs: &Vector<f64>, _t: f64, _u: &Self::U) -> Option<Self::U> {
+        None
+    }
 }
 
 pub enum Formulation {
@@ -22,6 +30,40 @@ pub enum Formulation {
     Weak,
 }
 
+// The contribution of the term -d/dx f(u) to the right-hand side of one component of the
+// unknown, in the scheme's formulation.
+//
+// `f` is the physical flux at the nodes, and `flux_jump` holds n * (f(u-) - f*) at the left
+// and right faces, which is what the numerical fluxes return. The strong form differentiates
+// f and lifts the jump; the weak form applies the stiffness operator to f and lifts n * f*.
+pub fn flux_divergence<GS: GalerkinScheme>(
+    elt: &Element<GS>,
+    elt_storage: &ElementStorage<GS::U, GS::F>,
+    operators: &Operators,
+    f: &Vector<f64>,
+    flux_jump: &Vector<f64>,
+) -> Vector<f64> {
+    match GS::FORMULATION {
+        Formulation::Strong => {
+            let dr_f = &operators.d_r * f;
+            let volume = (&elt_storage.r_x * -1.).elemul(&dr_f);
+            let lifted_flux = &operators.lift * &elt_storage.r_x_at_faces.elemul(flux_jump);
+            volume + lifted_flux
+        }
+        Formulation::Weak => {
+            let drw_f = &operators.d_r_w * f;
+            let volume = elt_storage.r_x.elemul(&drw_f);
+            let f_minus = vector![
+                f[0] * elt.left_outward_normal,
+                f[f.size() - 1] * elt.right_outward_normal
+            ];
+            let numerical_flux = f_minus - flux_jump;
+            let lifted_flux = &operators.lift * &elt_storage.r_x_at_faces.elemul(&numerical_flux);
+            volume - lifted_flux
+        }
+    }
+}
+
 pub fn compute_flux<GS: GalerkinScheme>(
     elt: &Element<GS>,
     elt_storage: &ElementStorage<GS::U, GS::F>,
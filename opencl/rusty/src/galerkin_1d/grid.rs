@@ -19,6 +19,40 @@ pub trait SpatialFlux {
     fn zero() -> Self::Unit;
 }
 
+// A spatial parameter which is constant on each element.
+impl SpatialFlux for f64 {
+    type Unit = f64;
+
+    fn first(&self) -> Self::Unit {
+        *self
+    }
+
+    fn last(&self) -> Self::Unit {
+        *self
+    }
+
+    fn zero() -> Self::Unit {
+        0.
+    }
+}
+
+// For equations without spatially varying parameters.
+impl SpatialFlux for () {
+    type Unit = ();
+
+    fn first(&self) -> Self::Unit {
+        ()
+    }
+
+    fn last(&self) -> Self::Unit {
+        ()
+    }
+
+    fn zero() -> Self::Unit {
+        ()
+    }
+}
+
 pub struct Element<GS: GalerkinScheme> {
     pub index: i32,
     pub x_left: f64,
@@ -134,6 +168,15 @@ impl ReferenceElement {
         let rs = Vector::new(rs);
         ReferenceElement { n_p, rs }
     }
+
+    // The smallest distance between two adjacent interpolation points in [-1, 1].
+    pub fn min_node_spacing(&self) -> f64 {
+        let mut rs: Vec<f64> = self.rs.iter().cloned().collect();
+        rs.sort_by(|a, b| a.partial_cmp(b).expect("NaN interpolation point"));
+        rs.windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .fold(f64::INFINITY, f64::min)
+    }
 }
 
 pub struct Grid<GS: GalerkinScheme> {
@@ -156,6 +199,26 @@ impl<GS: GalerkinScheme> fmt::Display for Grid<GS> {
     }
 }
 
+// The time step allowed by the CFL condition for a wave travelling at `wave_speed`, based
+// on the smallest distance between two nodes anywhere in the grid. Each element maps the
+// reference nodes onto [x_left, x_right], so its node spacing scales with its width.
+pub fn cfl_time_step<GS: GalerkinScheme>(
+    grid: &Grid<GS>,
+    reference_element: &ReferenceElement,
+    wave_speed: f64,
+    cfl: f64,
+) -> f64 {
+    assert!(wave_speed > 0.);
+    let dr_min = reference_element.min_node_spacing();
+    let dx_min = grid
+        .elements
+        .iter()
+        .map(|elt| (elt.x_right - elt.x_left) / 2. * dr_min)
+        .fold(f64::INFINITY, f64::min);
+    cfl * dx_min / wave_speed
+}
+
+// A grid of n_k equal-width elements between x_min and x_max.
 pub fn generate_grid<GS, Fx>(
     x_min: f64,
     x_max: f64,
@@ -172,69 +235,228 @@ where
 {
     assert!(x_max > x_min);
     let diff = (x_max - x_min) / (n_k as f64);
-    let transform = |left| {
+    let mut vertices: Vec<f64> = (0..n_k).map(|k| x_min + diff * (k as f64)).collect();
+    vertices.push(x_max);
+    generate_grid_from_vertices(
+        &vertices,
+        reference_element,
+        left_boundary,
+        right_boundary,
+        interior_flux,
+        f,
+    )
+}
+
+// A grid whose elements are the intervals between consecutive `vertices`, which must be
+// strictly increasing. Element k spans [vertices[k], vertices[k + 1]].
+pub fn generate_grid_from_vertices<GS, Fx>(
+    vertices: &[f64],
+    reference_element: &ReferenceElement,
+    left_boundary: Face<GS>,
+    right_boundary: Face<GS>,
+    interior_flux: <GS::FS as FluxScheme<GS::U, GS::F>>::Interior,
+    f: Fx,
+) -> Grid<GS>
+where
+    GS: GalerkinScheme,
+    Fx: Fn(&Vector<f64>) -> GS::F,
+{
+    build_grid(
+        vertices,
+        reference_element,
+        Some((left_boundary, right_boundary)),
+        interior_flux,
+        f,
+    )
+}
+
+// A periodic grid of n_k equal-width elements between x_min and x_max. The right face of
+// the last element is an interior face shared with the left face of the first element.
+pub fn generate_periodic_grid<GS, Fx>(
+    x_min: f64,
+    x_max: f64,
+    n_k: i32,
+    reference_element: &ReferenceElement,
+    interior_flux: <GS::FS as FluxScheme<GS::U, GS::F>>::Interior,
+    f: Fx,
+) -> Grid<GS>
+where
+    GS: GalerkinScheme,
+    Fx: Fn(&Vector<f64>) -> GS::F,
+{
+    assert!(x_max > x_min);
+    let diff = (x_max - x_min) / (n_k as f64);
+    let mut vertices: Vec<f64> = (0..n_k).map(|k| x_min + diff * (k as f64)).collect();
+    vertices.push(x_max);
+    generate_periodic_grid_from_vertices(&vertices, reference_element, interior_flux, f)
+}
+
+// A periodic grid whose elements are the intervals between consecutive `vertices`.
+pub fn generate_periodic_grid_from_vertices<GS, Fx>(
+    vertices: &[f64],
+    reference_element: &ReferenceElement,
+    interior_flux: <GS::FS as FluxScheme<GS::U, GS::F>>::Interior,
+    f: Fx,
+) -> Grid<GS>
+where
+    GS: GalerkinScheme,
+    Fx: Fn(&Vector<f64>) -> GS::F,
+{
+    build_grid(vertices, reference_element, None, interior_flux, f)
+}
+
+// Build a grid from its vertices. When `boundaries` is None the grid wraps around, so that
+// the first and last elements are neighbors.
+fn build_grid<GS, Fx>(
+    vertices: &[f64],
+    reference_element: &ReferenceElement,
+    boundaries: Option<(Face<GS>, Face<GS>)>,
+    interior_flux: <GS::FS as FluxScheme<GS::U, GS::F>>::Interior,
+    f: Fx,
+) -> Grid<GS>
+where
+    GS: GalerkinScheme,
+    Fx: Fn(&Vector<f64>) -> GS::F,
+{
+    assert!(vertices.len() >= 3, "a grid needs at least two elements");
+    assert!(
+        vertices.windows(2).all(|pair| pair[1] > pair[0]),
+        "grid vertices must be strictly increasing"
+    );
+    let n_k = vertices.len() as i32 - 1;
+    let transform = |left: f64, right: f64| {
         let s = (&reference_element.rs + 1.) / 2.;
-        let x = s * diff + left;
+        let x = s * (right - left) + left;
         x
     };
+
+    let (mut left_boundary, mut right_boundary) = match boundaries {
+        Some((left, right)) => (Some(left), Some(right)),
+        None => (None, None),
+    };
+    let interior_face = |j: i32| Face {
+        face_type: FaceType::Interior(j),
+        flux: FluxEnum::Interior(interior_flux),
+    };
     let mut elements = vec![];
-    let x_k = transform(x_min);
-    let spatial_flux = f(&x_k);
-    elements.push(Element {
-        index: 0,
-        x_left: x_min,
-        x_right: x_min + diff,
-        x_k,
-        left_face: Box::new(left_boundary),
-        right_face: Box::new(Face {
-            face_type: FaceType::Interior(1),
-            flux: FluxEnum::Interior(interior_flux),
-        }),
-        left_outward_normal: -1.,
-        right_outward_normal: 1.,
-        spatial_flux,
-    });
-    elements.extend((1..n_k - 1).map(|k| {
-        let left = x_min + diff * (k as f64);
-        let x_k = transform(left);
+    for k in 0..n_k {
+        let (left, right) = (vertices[k as usize], vertices[k as usize + 1]);
+        let x_k = transform(left, right);
         let spatial_flux = f(&x_k);
-        Element {
+        let left_face = if k == 0 {
+            left_boundary.take().unwrap_or_else(|| interior_face(n_k - 1))
+        } else {
+            interior_face(k - 1)
+        };
+        let right_face = if k == n_k - 1 {
+            right_boundary.take().unwrap_or_else(|| interior_face(0))
+        } else {
+            interior_face(k + 1)
+        };
+        elements.push(Element {
             index: k,
             x_left: left,
-            x_right: left + diff,
+            x_right: right,
             x_k,
-            left_face: Box::new(Face {
-                face_type: FaceType::Interior(k - 1),
-                flux: FluxEnum::Interior(interior_flux),
-            }),
-            right_face: Box::new(Face {
-                face_type: FaceType::Interior(k + 1),
-                flux: FluxEnum::Interior(interior_flux),
-            }),
+            left_face: Box::new(left_face),
+            right_face: Box::new(right_face),
             left_outward_normal: -1.,
             right_outward_normal: 1.,
             spatial_flux,
-        }
-    }));
-    let x_k = transform(x_max - diff);
-    let spatial_flux = f(&x_k);
-    elements.push(Element {
-        index: n_k - 1,
-        x_left: x_max - diff,
-        x_right: x_max,
-        x_k,
-        left_face: Box::new(Face {
-            face_type: FaceType::Interior(n_k - 2),
-            flux: FluxEnum::Interior(interior_flux),
-        }),
-        right_face: Box::new(right_boundary),
-        left_outward_normal: -1.,
-        right_outward_normal: 1.,
-        spatial_flux,
-    });
+        });
+    }
     Grid {
-        x_min,
-        x_max,
+        x_min: vertices[0],
+        x_max: vertices[n_k as usize],
         elements,
     }
 }
+
+// Vertices for n_k elements between x_min and x_max whose widths grow geometrically, each
+// element being `ratio` times as wide as the one to its left. A ratio below 1 refines
+// towards x_max instead.
+pub fn stretched_vertices(x_min: f64, x_max: f64, n_k: i32, ratio: f64) -> Vec<f64> {
+    assert!(x_max > x_min);
+    assert!(ratio > 0.);
+    let n = n_k as f64;
+    let first_width = if (ratio - 1.).abs() < 1e-12 {
+        (x_max - x_min) / n
+    } else {
+        (x_max - x_min) * (ratio - 1.) / (ratio.powf(n) - 1.)
+    };
+    let mut vertices = vec![x_min];
+    let mut width = first_width;
+    for _ in 1..n_k {
+        let last = vertices[vertices.len() - 1];
+        vertices.push(last + width);
+        width = width * ratio;
+    }
+    vertices.push(x_max);
+    vertices
+}
+
+// Vertices for n_k elements between x_min and x_max which are clustered around the interior
+// point `x_c`, using the sinh stretching of Vinokur. Larger values of `strength` give
+// smaller elements near x_c; as `strength` goes to zero the elements become uniform.
+pub fn clustered_vertices(
+    x_min: f64,
+    x_max: f64,
+    n_k: i32,
+    x_c: f64,
+    strength: f64,
+) -> Vec<f64> {
+    assert!(x_max > x_min);
+    assert!(x_c > x_min && x_c < x_max, "cluster point must lie inside the domain");
+    assert!(strength >= 0.);
+    let h = x_max - x_min;
+    let d = x_c - x_min;
+    let beta = strength;
+    let mut vertices: Vec<f64> = (0..n_k + 1)
+        .map(|k| {
+            let eta = k as f64 / n_k as f64;
+            if beta < 1e-12 {
+                return x_min + eta * h;
+            }
+            let a = 1. / (2. * beta)
+                * ((1. + (beta.exp() - 1.) * d / h) / (1. + ((-beta).exp() - 1.) * d / h)).ln();
+            x_min + d * (1. + (beta * (eta - a)).sinh() / (beta * a).sinh())
+        })
+        .collect();
+    vertices[0] = x_min;
+    vertices[n_k as usize] = x_max;
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clustered_vertices, stretched_vertices};
+
+    #[test]
+    fn test_stretched_vertices() {
+        let vertices = stretched_vertices(0., 1., 4, 2.);
+        assert_eq!(vertices.len(), 5);
+        assert_eq!(vertices[4], 1.);
+        let widths: Vec<f64> = vertices.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!((widths[0] - 1. / 15.).abs() < 1e-12);
+        for pair in widths.windows(2) {
+            assert!((pair[1] / pair[0] - 2.).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_clustered_vertices() {
+        let vertices = clustered_vertices(-1., 1., 16, 0.25, 4.);
+        assert_eq!(vertices[0], -1.);
+        assert_eq!(vertices[16], 1.);
+        assert!(vertices.windows(2).all(|w| w[1] > w[0]));
+
+        // The narrowest element touches the cluster point.
+        let widths: Vec<f64> = vertices.windows(2).map(|w| w[1] - w[0]).collect();
+        let narrowest = (0..16)
+            .min_by(|&i, &j| widths[i].partial_cmp(&widths[j]).unwrap())
+            .unwrap();
+        assert!(vertices[narrowest] <= 0.25 + widths[narrowest]);
+        assert!(vertices[narrowest + 1] >= 0.25 - widths[narrowest]);
+        assert!(widths[narrowest] < 2. / 16.);
+    }
+}
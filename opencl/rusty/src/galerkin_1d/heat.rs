@@ -0,0 +1,260 @@
+extern crate rulinalg;
+
+use self::rulinalg::vector::Vector;
+use galerkin_1d::diffusion::{diffusive_time_step, Diffusion, DiffusionFlux};
+use galerkin_1d::flux::FluxScheme;
+use galerkin_1d::flux::FreeflowFlux;
+use galerkin_1d::galerkin::Formulation;
+use galerkin_1d::galerkin::GalerkinScheme;
+use galerkin_1d::grid;
+use galerkin_1d::grid::{generate_periodic_grid, ReferenceElement};
+use galerkin_1d::operators::assemble_operators;
+use galerkin_1d::operators::Operators;
+use galerkin_1d::time_stepping::{integrate_operator, ClassicalRK4, TimeStepper};
+use galerkin_1d::unknowns::{initialize_storage, Components, Unknown};
+use std::f64::consts;
+use std::ops::{Add, Mul};
+
+// The heat equation u_t = nu u_xx.
+pub fn heat_1d<Fx, TS>(
+    u_0: Fx,
+    grid: &Grid,
+    reference_element: &ReferenceElement,
+    operators: &Operators,
+    nu: f64,
+    flux: DiffusionFlux,
+    final_time: f64,
+    stepper: &mut TS,
+) -> Vec<UStorage>
+where
+    Fx: Fn(&Vector<f64>) -> U,
+    TS: TimeStepper<U>,
+{
+    let mut storage: Vec<UStorage> =
+        initialize_storage(u_0, reference_element.n_p, grid, operators);
+
+    // The alternating LDG fluxes roughly double the spectral radius of the second
+    // derivative, so this is well below the usual 0.25.
+    let cfl = 0.05;
+    let dt: f64 = diffusive_time_step(grid, reference_element, nu, cfl);
+
+    // There are no first-order terms.
+    let operator = Diffusion {
+        hyperbolic: |_: &Element, elt_storage: &UStorage, _| elt_storage.u_k.clone() * 0.,
+        nu,
+        flux,
+        operators,
+    };
+
+    integrate_operator(
+        final_time,
+        dt,
+        grid,
+        &mut storage,
+        stepper,
+        &operator,
+        |_, _| {},
+        |_, _, _| {},
+    );
+
+    storage
+}
+
+#[derive(Debug, Clone)]
+pub struct U {
+    u: Vector<f64>,
+}
+
+impl Add for U {
+    type Output = U;
+
+    fn add(self, other: U) -> U {
+        U { u: self.u + other.u }
+    }
+}
+
+impl Mul<f64> for U {
+    type Output = U;
+
+    fn mul(self, other: f64) -> U {
+        U { u: self.u * other }
+    }
+}
+
+impl Unknown for U {
+    type Unit = f64;
+
+    fn first(&self) -> f64 {
+        self.u[0]
+    }
+
+    fn last(&self) -> f64 {
+        self.u[self.u.size() - 1]
+    }
+
+    fn zero() -> f64 {
+        0.0
+    }
+}
+
+impl Components for U {
+    fn components(&self) -> Vec<&Vector<f64>> {
+        vec![&self.u]
+    }
+
+    fn from_components(mut components: Vec<Vector<f64>>) -> U {
+        U {
+            u: components.remove(0),
+        }
+    }
+}
+
+type UStorage = grid::ElementStorage<U, ()>;
+
+type Grid = grid::Grid<Heat>;
+
+type Element = grid::Element<Heat>;
+
+// The hyperbolic face terms vanish, so every face uses the free-flow flux.
+pub struct HeatFluxScheme {}
+
+impl FluxScheme<U, ()> for HeatFluxScheme {
+    type Left = FreeflowFlux;
+    type Right = FreeflowFlux;
+    type Interior = FreeflowFlux;
+}
+
+pub struct Heat {}
+
+impl GalerkinScheme for Heat {
+    type U = U;
+    type F = ();
+    type FS = HeatFluxScheme;
+
+    const FORMULATION: Formulation = Formulation::Strong;
+}
+
+fn u_0(xs: &Vector<f64>) -> U {
+    U {
+        u: xs.iter().map(|x: &f64| x.sin()).collect(),
+    }
+}
+
+pub fn heat_1d_example() {
+    let n_p = 4;
+    let reference_element = ReferenceElement::legendre(n_p);
+    let grid: Grid = generate_periodic_grid(
+        0.0,
+        2. * consts::PI,
+        20,
+        &reference_element,
+        FreeflowFlux {},
+        |_| (),
+    );
+    let operators = assemble_operators::<U>(&reference_element);
+
+    // The sine mode decays like exp(-nu t).
+    let storages = heat_1d(
+        &u_0,
+        &grid,
+        &reference_element,
+        &operators,
+        1.0,
+        DiffusionFlux::LDG { tau: 0. },
+        0.5,
+        &mut ClassicalRK4 {},
+    );
+
+    for storage in storages.iter() {
+        println!("{:?}", &storage.u_k);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{heat_1d, u_0, Grid, U};
+    use galerkin_1d::diffusion::DiffusionFlux;
+    use galerkin_1d::flux::{FluxEnum, FreeflowFlux};
+    use galerkin_1d::grid::{
+        generate_grid, generate_periodic_grid, Face, FaceType, ReferenceElement,
+    };
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::time_stepping::ClassicalRK4;
+    use std::f64::consts;
+
+    const FLUXES: [DiffusionFlux; 2] = [
+        DiffusionFlux::LDG { tau: 0. },
+        DiffusionFlux::Central { tau: 10. },
+    ];
+
+    fn max_error(grid: &Grid, n_p: i32, flux: DiffusionFlux, t: f64) -> f64 {
+        let reference_element = ReferenceElement::legendre(n_p);
+        let operators = assemble_operators::<U>(&reference_element);
+        let storages = heat_1d(
+            &u_0,
+            grid,
+            &reference_element,
+            &operators,
+            1.0,
+            flux,
+            t,
+            &mut ClassicalRK4 {},
+        );
+        grid.elements
+            .iter()
+            .zip(storages.iter())
+            .flat_map(|(elt, storage)| {
+                elt.x_k
+                    .iter()
+                    .zip(storage.u_k.u.iter())
+                    .map(|(x, u)| (u - (-t).exp() * x.sin()).abs())
+                    .collect::<Vec<f64>>()
+            })
+            // A blown-up solution must not be hidden by f64::max ignoring NaN.
+            .fold(0., |acc: f64, e| if e.is_nan() || e > acc { e } else { acc })
+    }
+
+    #[test]
+    fn test_periodic_heat() {
+        let n_p = 4;
+        let reference_element = ReferenceElement::legendre(n_p);
+        for &flux in FLUXES.iter() {
+            let grid: Grid = generate_periodic_grid(
+                0.0,
+                2. * consts::PI,
+                10,
+                &reference_element,
+                FreeflowFlux {},
+                |_| (),
+            );
+            assert!(max_error(&grid, n_p, flux, 0.5) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_dirichlet_heat() {
+        // u = 0 at both ends, imposed by mirroring u about zero.
+        let n_p = 4;
+        let reference_element = ReferenceElement::legendre(n_p);
+        for &flux in FLUXES.iter() {
+            let dirichlet = || FaceType::Boundary(Box::new(|_, u_minus: f64| -u_minus), ());
+            let grid: Grid = generate_grid(
+                0.0,
+                consts::PI,
+                10,
+                &reference_element,
+                Face {
+                    face_type: dirichlet(),
+                    flux: FluxEnum::Left(FreeflowFlux {}),
+                },
+                Face {
+                    face_type: dirichlet(),
+                    flux: FluxEnum::Right(FreeflowFlux {}),
+                },
+                FreeflowFlux {},
+                |_| (),
+            );
+            assert!(max_error(&grid, n_p, flux, 0.5) < 1e-4);
+        }
+    }
+}
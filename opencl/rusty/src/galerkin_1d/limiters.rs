@@ -0,0 +1,170 @@
+extern crate rulinalg;
+
+use self::rulinalg::vector::Vector;
+use galerkin_1d::galerkin::GalerkinScheme;
+use galerkin_1d::grid::{Element, ElementStorage, FaceType, Grid};
+use galerkin_1d::operators::Operators;
+use galerkin_1d::unknowns::Components;
+
+// Slope limiters in the style of Cockburn and Shu. An element is left alone when its face
+// values are consistent with the cell averages of its neighbours; otherwise its solution is
+// replaced by a linear function through the cell average with a limited slope.
+#[derive(Clone, Copy, Debug)]
+pub enum SlopeLimiter {
+    // The MUSCL limiter, which takes the minmod of the element's own slope and the slopes
+    // to the neighbouring cell averages. It clips smooth extrema to first order.
+    Minmod,
+
+    // The TVB-modified minmod limiter. Slopes smaller than M h are accepted as they are, so
+    // smooth extrema keep their accuracy. M should be of the order of the second derivative
+    // of the solution near its extrema.
+    TVB { m: f64 },
+}
+
+impl SlopeLimiter {
+    // Limit every component of the solution on every element.
+    pub fn limit<GS>(
+        &self,
+        grid: &Grid<GS>,
+        storages: &mut Vec<ElementStorage<GS::U, GS::F>>,
+        operators: &Operators,
+    ) where
+        GS: GalerkinScheme,
+        GS::U: Components,
+    {
+        let n_components = storages[0].u_k.components().len();
+        let mut limited: Vec<Vec<Vector<f64>>> = vec![vec![]; storages.len()];
+
+        for c in 0..n_components {
+            let modes: Vec<Vector<f64>> = storages
+                .iter()
+                .map(|storage| &operators.v_inv * storage.u_k.components()[c])
+                .collect();
+            let averages: Vec<f64> = modes
+                .iter()
+                .map(|modes| modes[0] * operators.v[[0, 0]])
+                .collect();
+
+            for elt in grid.elements.iter() {
+                let k = elt.index as usize;
+                let u = storages[k].u_k.components()[c];
+                let (average_left, average_right) = neighbour_averages(elt, &averages);
+                limited[k].push(self.limit_element(
+                    elt,
+                    u,
+                    &modes[k],
+                    average_left,
+                    averages[k],
+                    average_right,
+                    operators,
+                ));
+            }
+        }
+
+        for (storage, components) in storages.iter_mut().zip(limited.into_iter()) {
+            storage.u_k = GS::U::from_components(components);
+        }
+    }
+
+    fn limit_element<GS: GalerkinScheme>(
+        &self,
+        elt: &Element<GS>,
+        u: &Vector<f64>,
+        modes: &Vector<f64>,
+        average_left: f64,
+        average: f64,
+        average_right: f64,
+        operators: &Operators,
+    ) -> Vector<f64> {
+        let h = elt.x_right - elt.x_left;
+        let bound = match *self {
+            SlopeLimiter::Minmod => 0.,
+            SlopeLimiter::TVB { m } => m * h * h,
+        };
+
+        // Leave the element alone if its face values need no limiting.
+        let u_left = u[0];
+        let u_right = u[u.size() - 1];
+        let left_limited = average
+            - modified_minmod(
+                &[average - u_left, average - average_left, average_right - average],
+                bound,
+            );
+        let right_limited = average
+            + modified_minmod(
+                &[u_right - average, average - average_left, average_right - average],
+                bound,
+            );
+        let eps = 1.0e-8;
+        if (left_limited - u_left).abs() < eps && (right_limited - u_right).abs() < eps {
+            return u.clone();
+        }
+
+        // The slope of the linear part of the solution.
+        let linear_modes: Vector<f64> = modes
+            .iter()
+            .enumerate()
+            .map(|(i, &mode)| if i < 2 { mode } else { 0. })
+            .collect();
+        let u_x = (&operators.d_r * (&operators.v * linear_modes))[0] * 2. / h;
+
+        let slope = modified_minmod(
+            &[
+                u_x,
+                (average_right - average) / h,
+                (average - average_left) / h,
+            ],
+            bound * 2. / h,
+        );
+        let x_0 = (elt.x_left + elt.x_right) / 2.;
+        elt.x_k.iter().map(|x| average + (x - x_0) * slope).collect()
+    }
+}
+
+// The cell averages to the left and right of `elt`. At a boundary the element's own average
+// is used, so the limiter sees a flat exterior.
+fn neighbour_averages<GS: GalerkinScheme>(elt: &Element<GS>, averages: &Vec<f64>) -> (f64, f64) {
+    let k = elt.index as usize;
+    let average_left = match elt.left_face.face_type {
+        FaceType::Interior(j) => averages[j as usize],
+        FaceType::Boundary(_, _) => averages[k],
+    };
+    let average_right = match elt.right_face.face_type {
+        FaceType::Interior(j) => averages[j as usize],
+        FaceType::Boundary(_, _) => averages[k],
+    };
+    (average_left, average_right)
+}
+
+// The minmod function: the argument of smallest magnitude if all arguments share a sign,
+// and zero otherwise.
+pub fn minmod(a: &[f64]) -> f64 {
+    let s = a[0].signum();
+    if a.iter().all(|x| x.signum() == s) {
+        s * a.iter().fold(a[0].abs(), |acc, x| acc.min(x.abs()))
+    } else {
+        0.
+    }
+}
+
+// The TVB-modified minmod function, which returns the first argument unchanged when its
+// magnitude is below `bound`.
+fn modified_minmod(a: &[f64], bound: f64) -> f64 {
+    if a[0].abs() <= bound {
+        a[0]
+    } else {
+        minmod(a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::minmod;
+
+    #[test]
+    fn test_minmod() {
+        assert_eq!(minmod(&[1., 0.5, 2.]), 0.5);
+        assert_eq!(minmod(&[-1., -0.5, -2.]), -0.5);
+        assert_eq!(minmod(&[1., -0.5, 2.]), 0.);
+    }
+}
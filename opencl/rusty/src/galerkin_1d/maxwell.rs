@@ -2,28 +2,53 @@ extern crate core;
 extern crate rulinalg;
 
 use self::core::ops::{Add, Div, Mul, Neg};
-use functions::range_kutta::{RKA, RKB, RKC};
+use checkpoint::file::{Checkpoint, CheckpointSchedule};
+use functions::filter::Filter;
+use galerkin_1d::checkpoint::{capture, restore};
 use galerkin_1d::flux::FluxEnum;
 use galerkin_1d::flux::FluxScheme;
 use galerkin_1d::flux::NumericalFlux;
 use galerkin_1d::flux::Side;
 use galerkin_1d::galerkin::compute_flux;
+use galerkin_1d::galerkin::flux_divergence;
 use galerkin_1d::galerkin::Formulation;
 use galerkin_1d::galerkin::GalerkinScheme;
 use galerkin_1d::grid;
 use galerkin_1d::grid::FaceType;
 use galerkin_1d::operators::{assemble_operators, Operators};
-use galerkin_1d::unknowns::{communicate, initialize_storage, Unknown};
+use galerkin_1d::time_stepping::{resume_operator, time_steps, LowStorageRK45, TimeStepper};
+use galerkin_1d::unknowns::{apply_filter, initialize_storage, Components, Unknown};
 use rulinalg::vector::Vector;
 use std::f64::consts;
-use std::iter::repeat;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct EH {
     E: Vector<f64>,
     H: Vector<f64>,
 }
 
+impl Add for EH {
+    type Output = EH;
+
+    fn add(self, other: EH) -> EH {
+        EH {
+            E: self.E + other.E,
+            H: self.H + other.H,
+        }
+    }
+}
+
+impl Mul<f64> for EH {
+    type Output = EH;
+
+    fn mul(self, other: f64) -> EH {
+        EH {
+            E: self.E * other,
+            H: self.H * other,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 struct EHUnit {
     E: f64,
@@ -96,6 +121,18 @@ impl Unknown for EH {
     }
 }
 
+impl Components for EH {
+    fn components(&self) -> Vec<&Vector<f64>> {
+        vec![&self.E, &self.H]
+    }
+
+    fn from_components(mut components: Vec<Vector<f64>>) -> EH {
+        let H = components.remove(1);
+        let E = components.remove(0);
+        EH { E, H }
+    }
+}
+
 #[derive(Copy, Clone)]
 struct Permittivity {
     epsilon: f64,
@@ -217,9 +254,9 @@ fn eh_0(xs: &Vector<f64>) -> EH {
     }
 }
 
-pub fn maxwell_1d_example() {
-    let n_p = 10;
-    let reference_element = grid::ReferenceElement::legendre(n_p);
+// The cavity [-1, 1] with perfectly conducting walls, filled with vacuum on the left and a
+// dielectric of permittivity 2 on the right.
+fn material_cavity(reference_element: &grid::ReferenceElement) -> Grid {
     let left_boundary_face = grid::Face {
         face_type: FaceType::Boundary(
             Box::new(move |_: f64, other_side: EHUnit| EHUnit {
@@ -246,128 +283,205 @@ pub fn maxwell_1d_example() {
         ),
         flux: FluxEnum::Right(MaxwellsExteriorFlux {}),
     };
-    let grid: grid::Grid<Maxwells> = grid::generate_grid(
+    grid::generate_grid(
         -1.0,
         1.0,
         8,
-        &reference_element,
+        reference_element,
         left_boundary_face,
         right_boundary_face,
         MaxwellsInteriorFlux {},
         &permittivity,
-    );
+    )
+}
+
+pub fn maxwell_1d_example() {
+    let n_p = 10;
+    let reference_element = grid::ReferenceElement::legendre(n_p);
+    let grid = material_cavity(&reference_element);
     let operators = assemble_operators::<EH>(&reference_element);
 
-    maxwell_1d(&eh_0, &grid, &reference_element, &operators);
+    let storage = maxwell_1d(
+        &eh_0,
+        &grid,
+        &reference_element,
+        &operators,
+        200.0,
+        &mut LowStorageRK45::new(),
+        None,
+        None,
+        None,
+    );
+    println!("here");
+    for elt in grid.elements.iter() {
+        let storage = &storage[elt.index as usize];
+        println!("{:?}", &storage.u_k.E);
+    }
+    println!("H:");
+    for elt in grid.elements.iter() {
+        let storage = &storage[elt.index as usize];
+        println!("{:?}", &storage.u_k.H);
+    }
 }
 
-fn maxwell_1d<Fx>(
+// Run to `final_time`, either from `eh_0` or, given `restart`, from a checkpoint of an
+// earlier run of the same problem. With a `schedule`, checkpoints are written as the run
+// goes.
+fn maxwell_1d<Fx, TS>(
     eh_0: Fx,
     grid: &Grid,
     reference_element: &grid::ReferenceElement,
     operators: &Operators,
-) where
+    final_time: f64,
+    stepper: &mut TS,
+    filter: Option<&Filter>,
+    restart: Option<&Checkpoint>,
+    schedule: Option<CheckpointSchedule>,
+) -> Vec<EHStorage>
+where
     Fx: Fn(&Vector<f64>) -> EH,
+    TS: TimeStepper<EH>,
 {
     //    let mut plotter = Plotter::create(-1.0, 1.0, -1.0, 1.0);
 
-    let final_time = 200.0;
+    // The fastest wave in the domain travels at the vacuum speed of light, 1.
     let cfl = 0.75;
-    let x_scale = 0.01;
-    let dt: f64 = 0.5 * cfl / (consts::PI * 2.) * x_scale;
-    let n_t = (final_time / dt).ceil() as i32;
-    let dt = final_time / n_t as f64;
-
-    let mut t: f64 = 0.0;
+    let dt: f64 = grid::cfl_time_step(grid, reference_element, 1.0, cfl);
+    // The length of the steps actually taken, which checkpoints record.
+    let (_, step_dt) = time_steps(final_time, dt);
 
     let mut storage: Vec<EHStorage> =
         initialize_storage(eh_0, reference_element.n_p, grid, operators);
-    let mut residuals: Vec<(Vector<f64>, Vector<f64>)> = repeat((
-        Vector::zeros(reference_element.n_p as usize + 1),
-        Vector::zeros(reference_element.n_p as usize + 1),
-    )).take(grid.elements.len())
-        .collect();
-
-    for epoch in 0..n_t {
-        for int_rk in 0..5 {
-            let t = t + RKC[int_rk] * dt;
-
-            communicate(t, grid, &storage);
-
-            for elt in (*grid).elements.iter() {
-                let mut storage = &mut storage[elt.index as usize];
-
-                let (residuals_e, residuals_h) = {
-                    let (residuals_e, residuals_h) = &(residuals[elt.index as usize]);
-                    let (rhs_e, rhs_h) =
-                        maxwell_rhs_1d(grid.elements.len() as i32, &elt, &storage, &operators);
-                    (
-                        residuals_e * RKA[int_rk] + rhs_e * dt,
-                        residuals_h * RKA[int_rk] + rhs_h * dt,
-                    )
-                };
-
-                let new_eh = {
-                    let eh_ref: &EH = &storage.u_k;
-                    EH {
-                        E: &eh_ref.E + &residuals_e * RKB[int_rk],
-                        H: &eh_ref.H + &residuals_h * RKB[int_rk],
-                    }
-                };
-
-                residuals[elt.index as usize] = (residuals_e, residuals_h);
-                storage.u_k = new_eh;
-            }
-        }
-        if epoch % 20 == 0 {
-            //            plotter.header();
-            //            for elt in (*grid).elements.iter() {
-            //                let storage = &storage[elt.index as usize];
-            //                plotter.plot(&elt.x_k, &storage.u_k.E);
-            //            }
-            //            plotter.replot();
+    let (start_step, start_time) = match restart {
+        Some(checkpoint) => {
+            restore(checkpoint, reference_element.n_p, step_dt, &mut storage, stepper)
+                .expect("could not resume from the checkpoint")
         }
-        t = t + dt;
-    }
-    println!("here");
-    for elt in (*grid).elements.iter() {
-        let storage = &storage[elt.index as usize];
-        println!("{:?}", &storage.u_k.E);
-    }
-    println!("H:");
-    for elt in (*grid).elements.iter() {
-        let storage = &storage[elt.index as usize];
-        println!("{:?}", &storage.u_k.H);
-    }
+        None => (0, 0.),
+    };
+
+    resume_operator(
+        start_step,
+        start_time,
+        final_time,
+        dt,
+        grid,
+        &mut storage,
+        stepper,
+        &|elt: &Element, elt_storage: &EHStorage, _| maxwell_rhs_1d(elt, elt_storage, operators),
+        |_, _| {},
+        |epoch, t, storage, stepper| {
+            if let Some(filter) = filter {
+                if filter.is_due(epoch + 1) {
+                    apply_filter(filter.matrix(), storage);
+                }
+            }
+            if let Some(schedule) = schedule {
+                if schedule.is_due(epoch + 1) {
+                    capture(reference_element.n_p, epoch + 1, t, step_dt, storage, stepper)
+                        .save(schedule.path())
+                        .expect("could not write the checkpoint");
+                }
+            }
+            if epoch % 20 == 0 {
+                //                plotter.header();
+                //                for elt in (*grid).elements.iter() {
+                //                    let storage = &storage[elt.index as usize];
+                //                    plotter.plot(&elt.x_k, &storage.u_k.E);
+                //                }
+                //                plotter.replot();
+            }
+        },
+    );
+
+    storage
 }
 
-fn maxwell_rhs_1d(
-    n_k: i32,
-    elt: &Element,
-    elt_storage: &EHStorage,
-    operators: &Operators,
-) -> (Vector<f64>, Vector<f64>) {
+fn maxwell_rhs_1d(elt: &Element, elt_storage: &EHStorage, operators: &Operators) -> EH {
     let (flux_left, flux_right) = compute_flux(elt, elt_storage);
 
-    let dr_h = &operators.d_r * &elt_storage.u_k.H;
     let flux_h = vector![flux_left.H, flux_right.H];
-    let lifted_flux_h = &operators.lift * &elt_storage.r_x_at_faces.elemul(&flux_h);
-    let rhs_e = ((&elt_storage.r_x * -1.).elemul(&dr_h) + lifted_flux_h) / elt.spatial_flux.epsilon;
+    let rhs_e = flux_divergence(elt, elt_storage, operators, &elt_storage.u_k.H, &flux_h)
+        / elt.spatial_flux.epsilon;
 
-    let dr_e = &operators.d_r * &elt_storage.u_k.E;
     let flux_e = vector![flux_left.E, flux_right.E];
-    let lifted_flux_e = &operators.lift * &elt_storage.r_x_at_faces.elemul(&flux_e);
-    let rhs_h = ((&elt_storage.r_x * -1.).elemul(&dr_e) + lifted_flux_e) / elt.spatial_flux.mu;
+    let rhs_h = flux_divergence(elt, elt_storage, operators, &elt_storage.u_k.E, &flux_e)
+        / elt.spatial_flux.mu;
 
-    (rhs_e, rhs_h)
+    EH { E: rhs_e, H: rhs_h }
 }
 
 #[cfg(test)]
 mod tests {
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use super::{eh_0, material_cavity, maxwell_1d, EHStorage, EH};
+    use checkpoint::file::{Checkpoint, CheckpointSchedule};
+    use galerkin_1d::checkpoint::restore;
+    use galerkin_1d::grid::{cfl_time_step, ReferenceElement};
     use galerkin_1d::maxwell::maxwell_1d_example;
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::time_stepping::{time_steps, LowStorageRK45};
+    use galerkin_1d::unknowns::initialize_storage;
 
     #[test]
     fn test() {
         maxwell_1d_example();
     }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let reference_element = ReferenceElement::legendre(6);
+        let grid = material_cavity(&reference_element);
+        let operators = assemble_operators::<EH>(&reference_element);
+        let final_time = 2.0;
+
+        let dir = tempdir().expect("could not create a temporary directory");
+        let path = dir.path().join("maxwell_1d.chk");
+        let schedule = CheckpointSchedule::new(&path, 7);
+        let uninterrupted = maxwell_1d(
+            &eh_0,
+            &grid,
+            &reference_element,
+            &operators,
+            final_time,
+            &mut LowStorageRK45::new(),
+            None,
+            None,
+            Some(schedule),
+        );
+
+        let checkpoint = Checkpoint::load(&path).expect("could not read the checkpoint");
+        assert!(checkpoint.step > 0 && checkpoint.time < final_time);
+        let resumed = maxwell_1d(
+            &eh_0,
+            &grid,
+            &reference_element,
+            &operators,
+            final_time,
+            &mut LowStorageRK45::new(),
+            None,
+            Some(&checkpoint),
+            None,
+        );
+
+        let bits = |storage: &Vec<EHStorage>| -> Vec<u64> {
+            storage
+                .iter()
+                .flat_map(|s| s.u_k.E.iter().chain(s.u_k.H.iter()))
+                .map(|x| x.to_bits())
+                .collect()
+        };
+        assert_eq!(bits(&resumed), bits(&uninterrupted));
+
+        // A run to another final time takes steps of another length, so it must not carry on
+        // from the checkpoint.
+        let mut storage = initialize_storage(&eh_0, reference_element.n_p, &grid, &operators);
+        let dt = cfl_time_step(&grid, &reference_element, 1.0, 0.75);
+        let (_, other_dt) = time_steps(final_time + 0.1, dt);
+        let mut stepper = LowStorageRK45::new();
+        let n_p = reference_element.n_p;
+        assert!(restore(&checkpoint, n_p, other_dt, &mut storage, &mut stepper).is_err());
+    }
 }
@@ -1,7 +1,17 @@
 pub mod advec;
+pub mod burgers;
+mod checkpoint;
+pub mod convergence;
+pub mod diffusion;
+pub mod euler;
 mod flux;
 mod galerkin;
 mod grid;
+pub mod heat;
+pub mod limiters;
 pub mod maxwell;
+pub mod norms;
 mod operators;
+pub mod shallow_water;
+mod time_stepping;
 mod unknowns;
@@ -0,0 +1,129 @@
+extern crate rulinalg;
+
+use self::rulinalg::vector::Vector;
+use galerkin_1d::galerkin::GalerkinScheme;
+use galerkin_1d::grid::{ElementStorage, Grid};
+use galerkin_1d::operators::Operators;
+use galerkin_1d::unknowns::Components;
+
+// Norms of the difference between the discrete solution and an exact solution, taken over
+// the whole grid.
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorNorms {
+    pub l2: f64,
+    pub l_inf: f64,
+    // The L2 norm of the derivative of the error.
+    pub h1_seminorm: f64,
+}
+
+// The errors in one component of the solution against `exact`, a function of x and t. The
+// error is sampled at the nodes and integrated with the element mass matrix (V V^T)^-1
+// scaled by the Jacobian, so these are the norms of its degree-N interpolant.
+pub fn error_norms<GS, Fx>(
+    grid: &Grid<GS>,
+    storages: &Vec<ElementStorage<GS::U, GS::F>>,
+    operators: &Operators,
+    component: usize,
+    exact: Fx,
+    t: f64,
+) -> ErrorNorms
+where
+    GS: GalerkinScheme,
+    GS::U: Components,
+    Fx: Fn(f64, f64) -> f64,
+{
+    let mass = &operators.mass;
+
+    let mut l2_squared = 0.;
+    let mut h1_squared = 0.;
+    let mut l_inf: f64 = 0.;
+    for elt in grid.elements.iter() {
+        let storage = &storages[elt.index as usize];
+        let u = storage.u_k.components()[component];
+        let error: Vector<f64> = elt
+            .x_k
+            .iter()
+            .zip(u.iter())
+            .map(|(&x, &u)| u - exact(x, t))
+            .collect();
+        let error_x = (&operators.d_r * &error).elemul(&storage.r_x);
+
+        // The map from the reference element is affine, so the Jacobian is constant.
+        let jacobian = 1. / storage.r_x[0];
+        l2_squared += jacobian * error.dot(&(mass * &error));
+        h1_squared += jacobian * error_x.dot(&(mass * &error_x));
+        l_inf = error.iter().fold(l_inf, |acc, e| {
+            if e.is_nan() || e.abs() > acc {
+                e.abs()
+            } else {
+                acc
+            }
+        });
+    }
+
+    ErrorNorms {
+        l2: l2_squared.sqrt(),
+        l_inf,
+        h1_seminorm: h1_squared.sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::error_norms;
+    use galerkin_1d::advec::{Advec, LinearFlux, U};
+    use galerkin_1d::flux::LaxFriedrichs;
+    use galerkin_1d::grid::{generate_periodic_grid, Grid, ReferenceElement};
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::unknowns::{initialize_storage, Components};
+    use rulinalg::vector::Vector;
+    use std::f64::consts;
+
+    #[test]
+    fn test_error_norms_of_constant_offset() {
+        // With u_h = sin(x) + 1 against sin(x), the error is exactly 1 everywhere.
+        let n_p = 6;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let grid: Grid<Advec> = generate_periodic_grid(
+            0.0,
+            2. * consts::PI,
+            10,
+            &reference_element,
+            LaxFriedrichs { alpha: 0. },
+            |xs| LinearFlux::sample(xs, |_| 1.),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let u_0 = |xs: &Vector<f64>| {
+            U::from_components(vec![xs.iter().map(|x| x.sin() + 1.).collect()])
+        };
+        let storages = initialize_storage(&u_0, n_p, &grid, &operators);
+        let norms = error_norms(&grid, &storages, &operators, 0, |x, _| x.sin(), 0.);
+        assert!((norms.l2 - (2. * consts::PI).sqrt()).abs() < 1e-10);
+        assert!((norms.l_inf - 1.).abs() < 1e-10);
+        assert!(norms.h1_seminorm < 1e-10);
+    }
+
+    #[test]
+    fn test_h1_seminorm_of_linear_error() {
+        // An error of x on [0, 1] has L2 norm 1 / sqrt(3) and H1 seminorm 1.
+        let n_p = 3;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let grid: Grid<Advec> = generate_periodic_grid(
+            0.0,
+            1.0,
+            5,
+            &reference_element,
+            LaxFriedrichs { alpha: 0. },
+            |xs| LinearFlux::sample(xs, |_| 1.),
+        );
+        let operators = assemble_operators::<U>(&reference_element);
+        let u_0 = |xs: &Vector<f64>| U::from_components(vec![xs.clone()]);
+        let storages = initialize_storage(&u_0, n_p, &grid, &operators);
+        let norms = error_norms(&grid, &storages, &operators, 0, |_, _| 0., 0.);
+        assert!((norms.l2 - 1. / 3f64.sqrt()).abs() < 1e-10);
+        assert!((norms.l_inf - 1.).abs() < 1e-10);
+        assert!((norms.h1_seminorm - 1.).abs() < 1e-10);
+    }
+}
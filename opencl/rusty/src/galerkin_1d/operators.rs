@@ -1,5 +1,6 @@
 extern crate rulinalg;
 
+use functions::filter::exponential_filter;
 use functions::vandermonde::{grad_vandermonde, vandermonde};
 use galerkin_1d::grid::ReferenceElement;
 use galerkin_1d::unknowns::Unknown;
@@ -9,14 +10,40 @@ pub struct Operators {
     // The Vandermonde matrix
     pub v: Matrix<f64>,
 
+    // The inverse Vandermonde matrix, mapping nodal values to modal coefficients
+    pub v_inv: Matrix<f64>,
+
+    // The mass matrix M = (V V^T)^-1
+    pub mass: Matrix<f64>,
+
+    // The inverse mass matrix V V^T
+    pub inv_mass: Matrix<f64>,
+
     // The D_r derivative matrix
     pub d_r: Matrix<f64>,
 
+    // The stiffness matrix S_r = M D_r, with entries (l_i, dl_j/dr).
+    pub s_r: Matrix<f64>,
+
+    // The weak-form stiffness operator M^-1 * S^T, where S is the stiffness matrix. Applied
+    // to the physical flux, this gives the volume term of the weak formulation.
+    pub d_r_w: Matrix<f64>,
+
     // The matrix lifting [a, b] to [a, ..., 0, ..., b] with length n_p, followed by the inverse
     // mass matrix.
     pub lift: Matrix<f64>,
 }
 
+impl Operators {
+    // The exponential filter V * diag(sigma) * V^-1, which leaves modes up to `cutoff`
+    // alone and damps the higher ones. `order` must be even.
+    pub fn filter(&self, cutoff: i32, order: i32) -> Matrix<f64> {
+        let n = self.v.cols() as i32 - 1;
+        let mode_orders: Vec<i32> = (0..n + 1).collect();
+        exponential_filter(&self.v, &self.v_inv, &mode_orders, n, cutoff, order)
+    }
+}
+
 pub fn assemble_operators<U>(reference_element: &ReferenceElement) -> Operators
 where
     U: Unknown,
@@ -31,12 +58,95 @@ where
         .expect("Non-invertible Vandermonde matrix");
     let v_r = grad_vandermonde(&rs, n_p);
     let d_r = &v_r * &v_inv;
+    let inv_mass = &v * v.transpose();
+    let mass = inv_mass
+        .clone()
+        .inverse()
+        .expect("Non-invertible inverse mass matrix");
+    let s_r = &mass * &d_r;
+    let d_r_w = (&v * v_r.transpose()) * &mass;
 
     let mut vals: Vec<f64> = vec![0.0; (n_p as usize + 1) * 2];
     vals[0] = 1.0;
     vals[2 * n_p as usize + 1] = 1.0;
     let e_mat = Matrix::new(n_p as usize + 1, 2, vals);
-    let lift = &v * &(v.transpose() * e_mat);
+    let lift = &inv_mass * e_mat;
+
+    Operators {
+        v,
+        v_inv,
+        mass,
+        inv_mass,
+        d_r,
+        s_r,
+        d_r_w,
+        lift,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::assemble_operators;
+    use galerkin_1d::advec::U;
+    use galerkin_1d::grid::ReferenceElement;
+    use rulinalg::matrix::{BaseMatrix, Matrix};
+    use rulinalg::norm::Euclidean;
+    use rulinalg::vector::Vector;
+
+    #[test]
+    fn test_weak_derivative_integrates_by_parts() {
+        // M^-1 S^T = -D_r + M^-1 (e_N e_N^T - e_0 e_0^T)
+        let n_p = 6;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let operators = assemble_operators::<U>(&reference_element);
+        let n = n_p as usize;
+        let mut boundary: Matrix<f64> = Matrix::zeros(2, n + 1);
+        boundary[[0, 0]] = -1.;
+        boundary[[1, n]] = 1.;
+        let expected = &operators.lift * &boundary - &operators.d_r;
+        let diff = &operators.d_r_w - &expected;
+        assert!(diff.data().iter().all(|x| x.abs() < 1e-8));
+    }
+
+    #[test]
+    fn test_mass_and_stiffness() {
+        let n_p = 5;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let operators = assemble_operators::<U>(&reference_element);
+        let ones = Vector::ones(n_p as usize + 1);
+        let r = reference_element.rs.clone();
+
+        // The integral of 1 over [-1, 1] is 2.
+        assert!((ones.dot(&(&operators.mass * &ones)) - 2.).abs() < 1e-10);
+
+        // M and its inverse
+        let identity = &operators.mass * &operators.inv_mass;
+        let diff = identity - Matrix::identity(n_p as usize + 1);
+        assert!(diff.data().iter().all(|x| x.abs() < 1e-8));
+
+        // The integral of r d(r)/dr over [-1, 1] is 0, and that of 1 d(r)/dr is 2.
+        assert!(r.dot(&(&operators.s_r * &r)).abs() < 1e-10);
+        assert!((ones.dot(&(&operators.s_r * &r)) - 2.).abs() < 1e-10);
+    }
 
-    Operators { v, d_r, lift }
+    #[test]
+    fn test_filter_damps_high_modes() {
+        let n_p = 6;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let operators = assemble_operators::<U>(&reference_element);
+        let filter = operators.filter(2, 8);
+        for i in 0..(n_p + 1) as usize {
+            let mode = operators.v.col(i).iter().cloned().collect::<Vector<f64>>();
+            let filtered = &filter * &mode;
+            if i <= 2 {
+                // Modes up to the cutoff pass through unchanged.
+                assert!((filtered - mode).norm(Euclidean) < 1e-10);
+            } else if i == n_p as usize {
+                // The highest mode is damped to machine precision.
+                assert!(filtered.norm(Euclidean) < 1e-10);
+            }
+        }
+    }
 }
@@ -0,0 +1,912 @@
+extern crate rulinalg;
+
+use self::rulinalg::vector::Vector;
+use galerkin_1d::flux::FluxEnum;
+use galerkin_1d::flux::FluxScheme;
+use galerkin_1d::flux::NumericalFlux;
+use galerkin_1d::flux::Side;
+use galerkin_1d::flux::{ConservationFlux, ConservationLaw, RiemannSolver};
+use galerkin_1d::galerkin::compute_flux;
+use galerkin_1d::galerkin::flux_divergence;
+use galerkin_1d::galerkin::Formulation;
+use galerkin_1d::galerkin::GalerkinScheme;
+use galerkin_1d::grid;
+use galerkin_1d::grid::FaceType;
+use galerkin_1d::grid::{cfl_time_step, generate_grid, ReferenceElement};
+use galerkin_1d::limiters::SlopeLimiter;
+use galerkin_1d::operators::{assemble_operators, Operators};
+use galerkin_1d::time_stepping::{integrate_adaptive, SSPRK3, TimeStepper};
+use galerkin_1d::unknowns::{initialize_storage, Components, Unknown};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// The acceleration due to gravity.
+const GRAVITY: f64 = 9.81;
+
+// Water shallower than this is treated as dry, and has no velocity.
+const DRY_TOLERANCE: f64 = 1.0e-8;
+
+// The conserved variables of the 1D shallow water equations: depth and discharge.
+#[derive(Debug, Clone)]
+pub struct ShallowWaterState {
+    pub h: Vector<f64>,
+    pub hu: Vector<f64>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ShallowWaterUnit {
+    pub h: f64,
+    pub hu: f64,
+}
+
+impl ShallowWaterUnit {
+    // The velocity, taken to be zero where the bed is dry.
+    fn velocity(&self) -> f64 {
+        if self.h > DRY_TOLERANCE {
+            self.hu / self.h
+        } else {
+            0.
+        }
+    }
+
+    // The speed sqrt(g h) of gravity waves relative to the flow.
+    fn celerity(&self) -> f64 {
+        (GRAVITY * self.h.max(0.)).sqrt()
+    }
+
+    // The physical flux (h u, h u^2 + g h^2 / 2).
+    fn flux(&self) -> ShallowWaterUnit {
+        let u = self.velocity();
+        ShallowWaterUnit {
+            h: self.h * u,
+            hu: self.h * u * u + GRAVITY / 2. * self.h * self.h,
+        }
+    }
+}
+
+impl Add for ShallowWaterUnit {
+    type Output = ShallowWaterUnit;
+
+    fn add(self, other: ShallowWaterUnit) -> ShallowWaterUnit {
+        ShallowWaterUnit {
+            h: self.h + other.h,
+            hu: self.hu + other.hu,
+        }
+    }
+}
+
+impl Sub for ShallowWaterUnit {
+    type Output = ShallowWaterUnit;
+
+    fn sub(self, other: ShallowWaterUnit) -> ShallowWaterUnit {
+        ShallowWaterUnit {
+            h: self.h - other.h,
+            hu: self.hu - other.hu,
+        }
+    }
+}
+
+impl Mul<f64> for ShallowWaterUnit {
+    type Output = ShallowWaterUnit;
+
+    fn mul(self, other: f64) -> ShallowWaterUnit {
+        ShallowWaterUnit {
+            h: self.h * other,
+            hu: self.hu * other,
+        }
+    }
+}
+
+impl Div<f64> for ShallowWaterUnit {
+    type Output = ShallowWaterUnit;
+
+    fn div(self, other: f64) -> ShallowWaterUnit {
+        ShallowWaterUnit {
+            h: self.h / other,
+            hu: self.hu / other,
+        }
+    }
+}
+
+impl Neg for ShallowWaterUnit {
+    type Output = ShallowWaterUnit;
+
+    fn neg(self) -> ShallowWaterUnit {
+        ShallowWaterUnit {
+            h: -self.h,
+            hu: -self.hu,
+        }
+    }
+}
+
+impl Add for ShallowWaterState {
+    type Output = ShallowWaterState;
+
+    fn add(self, other: ShallowWaterState) -> ShallowWaterState {
+        ShallowWaterState {
+            h: self.h + other.h,
+            hu: self.hu + other.hu,
+        }
+    }
+}
+
+impl Mul<f64> for ShallowWaterState {
+    type Output = ShallowWaterState;
+
+    fn mul(self, other: f64) -> ShallowWaterState {
+        ShallowWaterState {
+            h: self.h * other,
+            hu: self.hu * other,
+        }
+    }
+}
+
+impl Unknown for ShallowWaterState {
+    type Unit = ShallowWaterUnit;
+
+    fn first(&self) -> Self::Unit {
+        ShallowWaterUnit {
+            h: self.h[0],
+            hu: self.hu[0],
+        }
+    }
+
+    fn last(&self) -> Self::Unit {
+        let n = self.h.size() - 1;
+        ShallowWaterUnit {
+            h: self.h[n],
+            hu: self.hu[n],
+        }
+    }
+
+    fn zero() -> ShallowWaterUnit {
+        ShallowWaterUnit { h: 0., hu: 0. }
+    }
+}
+
+impl Components for ShallowWaterState {
+    fn components(&self) -> Vec<&Vector<f64>> {
+        vec![&self.h, &self.hu]
+    }
+
+    fn from_components(mut components: Vec<Vector<f64>>) -> ShallowWaterState {
+        let hu = components.remove(1);
+        let h = components.remove(0);
+        ShallowWaterState { h, hu }
+    }
+}
+
+// The elevation b(x) of the bottom at the nodes of an element. The water surface is at
+// h + b.
+#[derive(Clone, Debug)]
+pub struct Bathymetry {
+    pub b: Vector<f64>,
+}
+
+impl Bathymetry {
+    // The bottom `b` sampled at the nodes `xs`.
+    pub fn sample<Fb>(xs: &Vector<f64>, b: Fb) -> Bathymetry
+    where
+        Fb: Fn(f64) -> f64,
+    {
+        Bathymetry {
+            b: xs.iter().map(|&x| b(x)).collect(),
+        }
+    }
+}
+
+impl grid::SpatialFlux for Bathymetry {
+    type Unit = f64;
+
+    fn first(&self) -> Self::Unit {
+        self.b[0]
+    }
+
+    fn last(&self) -> Self::Unit {
+        self.b[self.b.size() - 1]
+    }
+
+    fn zero() -> Self::Unit {
+        0.
+    }
+}
+
+type Grid = grid::Grid<ShallowWater>;
+
+type Element = grid::Element<ShallowWater>;
+
+type ShallowWaterStorage = grid::ElementStorage<ShallowWaterState, Bathymetry>;
+
+// The shallow water equations as a conservation law, for use with the generic Riemann
+// solvers. The bottom enters only through the source term, so it is ignored here.
+#[derive(Clone, Copy, Debug)]
+pub struct ShallowWaterLaw {}
+
+impl ConservationLaw<ShallowWaterState, Bathymetry> for ShallowWaterLaw {
+    fn flux(&self, side: &Side<ShallowWaterState, Bathymetry>) -> ShallowWaterUnit {
+        side.u.flux()
+    }
+
+    fn wave_speeds(&self, side: &Side<ShallowWaterState, Bathymetry>) -> (f64, f64) {
+        let (u, c) = (side.u.velocity(), side.u.celerity());
+        (u - c, u + c)
+    }
+
+    // Roe's linearization about the depth-weighted average velocity, with Harten's entropy
+    // fix. Between two dry states there is nothing to dissipate.
+    fn roe_dissipation(
+        &self,
+        left: &Side<ShallowWaterState, Bathymetry>,
+        right: &Side<ShallowWaterState, Bathymetry>,
+    ) -> ShallowWaterUnit {
+        let (q_l, q_r) = (left.u, right.u);
+        let (w_l, w_r) = (q_l.h.max(0.).sqrt(), q_r.h.max(0.).sqrt());
+        if w_l + w_r <= 0. {
+            return ShallowWaterState::zero();
+        }
+        let u = (w_l * q_l.velocity() + w_r * q_r.velocity()) / (w_l + w_r);
+        let c = (GRAVITY * (q_l.h + q_r.h).max(0.) / 2.).sqrt();
+
+        // The wave strengths.
+        let d = q_r - q_l;
+        let alpha_1 = ((u + c) * d.h - d.hu) / (2. * c);
+        let alpha_2 = (d.hu - (u - c) * d.h) / (2. * c);
+
+        let delta = 0.1 * c;
+        let entropy_fix = |lambda: f64| {
+            if lambda.abs() < delta {
+                (lambda * lambda + delta * delta) / (2. * delta)
+            } else {
+                lambda.abs()
+            }
+        };
+
+        let k_1 = ShallowWaterUnit { h: 1., hu: u - c };
+        let k_2 = ShallowWaterUnit { h: 1., hu: u + c };
+        k_1 * (entropy_fix(u - c) * alpha_1) + k_2 * (entropy_fix(u + c) * alpha_2)
+    }
+}
+
+// A Riemann solver applied to the hydrostatic reconstruction of Audusse et al. Each side's
+// depth is cut down to what lies above the higher of the two bottoms before the solver sees
+// it. This keeps the depth non-negative at wet/dry fronts, and together with the pressure
+// correction below keeps a lake at rest steady across steps in the bottom. Returns the
+// strong-form face term n * (f(u-) - f*).
+#[derive(Clone, Copy, Debug)]
+pub struct ShallowWaterFlux {
+    pub solver: RiemannSolver,
+}
+
+impl NumericalFlux<ShallowWaterState, Bathymetry> for ShallowWaterFlux {
+    fn flux(
+        &self,
+        minus: Side<ShallowWaterState, Bathymetry>,
+        plus: Side<ShallowWaterState, Bathymetry>,
+        outward_normal: f64,
+    ) -> ShallowWaterUnit {
+        let b_star = minus.f.max(plus.f);
+        let reconstruct = |side: &Side<ShallowWaterState, Bathymetry>| {
+            let h = (side.u.h + side.f - b_star).max(0.);
+            let reconstructed: Side<ShallowWaterState, Bathymetry> = Side {
+                u: ShallowWaterUnit {
+                    h,
+                    hu: h * side.u.velocity(),
+                },
+                f: b_star,
+            };
+            reconstructed
+        };
+        let (minus_star, plus_star) = (reconstruct(&minus), reconstruct(&plus));
+
+        let flux = ConservationFlux {
+            law: ShallowWaterLaw {},
+            solver: self.solver,
+        };
+        let f_star = if outward_normal > 0. {
+            flux.interface_flux(&minus_star, &plus_star)
+        } else {
+            flux.interface_flux(&plus_star, &minus_star)
+        };
+
+        // The hydrostatic pressure of the water cut away from the interior side.
+        let correction = ShallowWaterUnit {
+            h: 0.,
+            hu: GRAVITY / 2. * (minus.u.h * minus.u.h - minus_star.u.h * minus_star.u.h),
+        };
+        (minus.u.flux() - f_star - correction) * outward_normal
+    }
+}
+
+pub struct ShallowWaterFluxScheme {}
+
+impl FluxScheme<ShallowWaterState, Bathymetry> for ShallowWaterFluxScheme {
+    type Left = ShallowWaterFlux;
+    type Right = ShallowWaterFlux;
+    type Interior = ShallowWaterFlux;
+}
+
+pub struct ShallowWater {}
+
+impl GalerkinScheme for ShallowWater {
+    type U = ShallowWaterState;
+    type F = Bathymetry;
+    type FS = ShallowWaterFluxScheme;
+
+    const FORMULATION: Formulation = Formulation::Strong;
+}
+
+// A solid wall: the exterior state mirrors the interior one with the discharge reversed.
+// `b` is the bottom elevation at the wall.
+pub fn reflective_boundary(b: f64) -> FaceType<ShallowWater> {
+    FaceType::Boundary(
+        Box::new(move |_, other_side: ShallowWaterUnit| ShallowWaterUnit {
+            h: other_side.h,
+            hu: -other_side.hu,
+        }),
+        b,
+    )
+}
+
+// An open boundary through which waves leave the domain without reflection.
+pub fn transmissive_boundary(b: f64) -> FaceType<ShallowWater> {
+    FaceType::Boundary(Box::new(move |_, other_side: ShallowWaterUnit| other_side), b)
+}
+
+pub fn shallow_water_1d<Fx, TS>(
+    u_0: Fx,
+    grid: &Grid,
+    reference_element: &ReferenceElement,
+    operators: &Operators,
+    final_time: f64,
+    stepper: &mut TS,
+    limiter: Option<SlopeLimiter>,
+) -> Vec<ShallowWaterStorage>
+where
+    Fx: Fn(&Vector<f64>) -> ShallowWaterState,
+    TS: TimeStepper<ShallowWaterState>,
+{
+    let mut storage: Vec<ShallowWaterStorage> =
+        initialize_storage(u_0, reference_element.n_p, grid, operators);
+
+    // The CFL number is small enough for the cell averages of the depth to stay
+    // non-negative. Flow down a slope speeds up, so the step is chosen afresh each time.
+    let cfl = 0.2;
+
+    integrate_adaptive(
+        final_time,
+        grid,
+        &mut storage,
+        stepper,
+        |elt, elt_storage, _| shallow_water_rhs_1d(elt, elt_storage, operators),
+        |storages| cfl_time_step(grid, reference_element, max_speed(storages), cfl),
+        |grid, storages| {
+            if let Some(limiter) = limiter {
+                limit_surface(&limiter, grid, storages, operators);
+            }
+            limit_positivity(storages, operators);
+        },
+        |_, _, _| {},
+    );
+
+    storage
+}
+
+// The fastest signal speed anywhere in the solution. Water running onto a dry bed moves at
+// u + 2 sqrt(g h), so that is the speed the time step allows for.
+fn max_speed(storages: &Vec<ShallowWaterStorage>) -> f64 {
+    storages
+        .iter()
+        .flat_map(|storage| {
+            let u_k = &storage.u_k;
+            u_k.h
+                .iter()
+                .zip(u_k.hu.iter())
+                .map(|(&h, &hu)| {
+                    let q = ShallowWaterUnit { h, hu };
+                    q.velocity().abs() + 2. * q.celerity()
+                })
+                .collect::<Vec<f64>>()
+        })
+        .fold(0., f64::max)
+}
+
+fn shallow_water_rhs_1d(
+    elt: &Element,
+    elt_storage: &ShallowWaterStorage,
+    operators: &Operators,
+) -> ShallowWaterState {
+    let (flux_left, flux_right) = compute_flux(elt, elt_storage);
+
+    let q = &elt_storage.u_k;
+    let b = &wetted_bottom(q, &elt.spatial_flux.b);
+    let u: Vector<f64> = q.h
+        .iter()
+        .zip(q.hu.iter())
+        .map(|(&h, &hu)| ShallowWaterUnit { h, hu }.velocity())
+        .collect();
+    let f_h = q.h.elemul(&u);
+    let f_hu = f_h.elemul(&u) + q.h.elemul(&q.h) * (GRAVITY / 2.);
+
+    // The bed slope term -g h b_x, written after Xing and Shu as -g (h + b) b_x + g (b^2/2)_x.
+    // Taking both derivatives with the same differentiation matrix as the flux makes the
+    // source cancel the gradient of g h^2 / 2 exactly when h + b is constant, which is why
+    // it is computed here rather than through `GalerkinScheme::source`.
+    let b_x = (&operators.d_r * b).elemul(&elt_storage.r_x);
+    let b_squared_x = (&operators.d_r * (b.elemul(b) * 0.5)).elemul(&elt_storage.r_x);
+    let source = (b_squared_x - (&q.h + b).elemul(&b_x)) * GRAVITY;
+
+    let h = flux_divergence(
+        elt,
+        elt_storage,
+        operators,
+        &f_h,
+        &vector![flux_left.h, flux_right.h],
+    );
+    let hu = flux_divergence(
+        elt,
+        elt_storage,
+        operators,
+        &f_hu,
+        &vector![flux_left.hu, flux_right.hu],
+    ) + source;
+
+    ShallowWaterState { h, hu }
+}
+
+// The bottom as the water in an element sees it. At dry nodes the bottom is cut down to the
+// surface of the wet part of the element, so that along the shoreline of a lake at rest the
+// surface is flat across the whole element. Entirely dry elements keep their bottom.
+fn wetted_bottom(q: &ShallowWaterState, b: &Vector<f64>) -> Vector<f64> {
+    let surface = q.h
+        .iter()
+        .zip(b.iter())
+        .filter(|&(&h, _)| h > DRY_TOLERANCE)
+        .map(|(&h, &b)| h + b)
+        .fold(None, |acc: Option<f64>, eta| Some(acc.map_or(eta, |acc| acc.max(eta))));
+    match surface {
+        Some(surface) => q.h
+            .iter()
+            .zip(b.iter())
+            .map(|(&h, &b)| if h > DRY_TOLERANCE { b } else { b.min(surface) })
+            .collect(),
+        None => b.clone(),
+    }
+}
+
+// Apply a slope limiter to the surface elevation h + b rather than to the depth, so that the
+// flat surface of a lake at rest is left alone. Entirely dry elements have no surface to
+// limit.
+fn limit_surface(
+    limiter: &SlopeLimiter,
+    grid: &Grid,
+    storages: &mut Vec<ShallowWaterStorage>,
+    operators: &Operators,
+) {
+    let bottoms: Vec<Vector<f64>> = grid.elements
+        .iter()
+        .zip(storages.iter())
+        .map(|(elt, storage)| wetted_bottom(&storage.u_k, &elt.spatial_flux.b))
+        .collect();
+    let dry: Vec<Option<ShallowWaterState>> = storages
+        .iter()
+        .map(|storage| {
+            if storage.u_k.h.iter().all(|&h| h <= DRY_TOLERANCE) {
+                Some(storage.u_k.clone())
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    for (b, storage) in bottoms.iter().zip(storages.iter_mut()) {
+        storage.u_k.h = &storage.u_k.h + b;
+    }
+    limiter.limit(grid, storages, operators);
+    for (b, storage) in bottoms.iter().zip(storages.iter_mut()) {
+        storage.u_k.h = &storage.u_k.h - b;
+    }
+    for (storage, dry) in storages.iter_mut().zip(dry.into_iter()) {
+        if let Some(u_k) = dry {
+            storage.u_k = u_k;
+        }
+    }
+}
+
+// The positivity-preserving limiter of Xing, Zhang and Shu. Where the depth dips below zero
+// at a node, the element's solution is contracted towards its cell average, which the time
+// step keeps non-negative, until the smallest nodal depth is zero. Elements whose average is
+// dry are replaced by still water.
+pub fn limit_positivity(storages: &mut Vec<ShallowWaterStorage>, operators: &Operators) {
+    let average = |u: &Vector<f64>| (&operators.v_inv * u)[0] * operators.v[[0, 0]];
+    for storage in storages.iter_mut() {
+        let limited = {
+            let q = &storage.u_k;
+            let (h_average, hu_average) = (average(&q.h), average(&q.hu));
+            let h_min = q.h.iter().cloned().fold(f64::INFINITY, f64::min);
+            if h_average <= DRY_TOLERANCE {
+                ShallowWaterState {
+                    h: Vector::ones(q.h.size()) * h_average.max(0.),
+                    hu: Vector::zeros(q.hu.size()),
+                }
+            } else if h_min < 0. {
+                let theta = h_average / (h_average - h_min);
+                ShallowWaterState {
+                    h: (&q.h - h_average) * theta + h_average,
+                    hu: (&q.hu - hu_average) * theta + hu_average,
+                }
+            } else {
+                continue;
+            }
+        };
+        storage.u_k = limited;
+    }
+}
+
+fn from_depth<Fh>(xs: &Vector<f64>, depth: Fh) -> ShallowWaterState
+where
+    Fh: Fn(f64) -> f64,
+{
+    ShallowWaterState {
+        h: xs.iter().map(|&x| depth(x)).collect(),
+        hu: Vector::zeros(xs.size()),
+    }
+}
+
+// The dam-break problem on [-1, 1]: still water of depth 1 held back by a dam at x = 0, with
+// a dry bed in front of it. Its exact solution is Ritter's rarefaction.
+pub fn dry_dam_break(xs: &Vector<f64>) -> ShallowWaterState {
+    from_depth(xs, |x| if x < 0. { 1. } else { 0. })
+}
+
+// As `dry_dam_break`, but with water of depth 0.5 in front of the dam, so that a bore runs
+// downstream. Usually run to t = 0.1.
+pub fn wet_dam_break(xs: &Vector<f64>) -> ShallowWaterState {
+    from_depth(xs, |x| if x < 0. { 1. } else { 0.5 })
+}
+
+// A smooth hump of height 0.5 in the bottom, centred on x = 0.
+pub fn hump(x: f64) -> f64 {
+    0.5 * (-25. * x * x).exp()
+}
+
+// Still water with its surface at `surface` over the bottom `b`. Where the bottom rises
+// above the surface the bed is dry.
+pub fn lake_at_rest<Fb>(xs: &Vector<f64>, surface: f64, b: Fb) -> ShallowWaterState
+where
+    Fb: Fn(f64) -> f64,
+{
+    from_depth(xs, |x| (surface - b(x)).max(0.))
+}
+
+pub fn shallow_water_1d_example() {
+    let n_p = 2;
+    let reference_element = ReferenceElement::legendre(n_p);
+    let flux = ShallowWaterFlux {
+        solver: RiemannSolver::HLL,
+    };
+    let left_boundary_face = grid::Face {
+        face_type: reflective_boundary(0.),
+        flux: FluxEnum::Left(flux),
+    };
+    let right_boundary_face = grid::Face {
+        face_type: transmissive_boundary(0.),
+        flux: FluxEnum::Right(flux),
+    };
+    let grid: Grid = generate_grid(
+        -1.0,
+        1.0,
+        100,
+        &reference_element,
+        left_boundary_face,
+        right_boundary_face,
+        flux,
+        |xs| Bathymetry::sample(xs, |_| 0.),
+    );
+    let operators = assemble_operators::<ShallowWaterState>(&reference_element);
+
+    let storages = shallow_water_1d(
+        &dry_dam_break,
+        &grid,
+        &reference_element,
+        &operators,
+        0.1,
+        &mut SSPRK3 {},
+        Some(SlopeLimiter::Minmod),
+    );
+
+    for storage in storages.iter() {
+        println!("{:?}", &storage.u_k.h);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::{
+        dry_dam_break, hump, lake_at_rest, reflective_boundary, shallow_water_1d,
+        transmissive_boundary, wet_dam_break, Bathymetry, Grid, ShallowWater, ShallowWaterFlux,
+        ShallowWaterLaw, ShallowWaterState, ShallowWaterUnit, GRAVITY,
+    };
+    use galerkin_1d::flux::{ConservationFlux, FluxEnum, RiemannSolver, Side};
+    use galerkin_1d::galerkin::GalerkinScheme;
+    use galerkin_1d::grid;
+    use galerkin_1d::grid::{cfl_time_step, generate_grid, Face, FaceType, ReferenceElement};
+    use galerkin_1d::limiters::SlopeLimiter;
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::time_stepping::{SpatialOperator, SSPRK3, TimeStepper};
+    use rulinalg::vector::Vector;
+
+    const SOLVERS: [RiemannSolver; 3] = [
+        RiemannSolver::Rusanov,
+        RiemannSolver::HLL,
+        RiemannSolver::Roe,
+    ];
+
+    // A grid on [-1, 1] with the given boundary conditions at both ends.
+    fn grid<Fb, Fc>(
+        n_k: i32,
+        reference_element: &ReferenceElement,
+        solver: RiemannSolver,
+        boundary: Fc,
+        b: Fb,
+    ) -> Grid
+    where
+        Fb: Fn(f64) -> f64,
+        Fc: Fn(f64) -> FaceType<ShallowWater>,
+    {
+        let flux = ShallowWaterFlux { solver };
+        generate_grid(
+            -1.0,
+            1.0,
+            n_k,
+            reference_element,
+            Face {
+                face_type: boundary(b(-1.)),
+                flux: FluxEnum::Left(flux),
+            },
+            Face {
+                face_type: boundary(b(1.)),
+                flux: FluxEnum::Right(flux),
+            },
+            flux,
+            |xs| Bathymetry::sample(xs, &b),
+        )
+    }
+
+    #[test]
+    fn test_fluxes_are_consistent() {
+        let q = ShallowWaterUnit { h: 1.3, hu: 0.7 };
+        let side: Side<ShallowWaterState, Bathymetry> = Side { u: q, f: 0. };
+        let f = q.flux();
+        for &solver in SOLVERS.iter() {
+            let flux = ConservationFlux {
+                law: ShallowWaterLaw {},
+                solver,
+            };
+            let f_star = flux.interface_flux(&side, &side);
+            assert!((f_star.h - f.h).abs() < 1e-12);
+            assert!((f_star.hu - f.hu).abs() < 1e-12);
+        }
+    }
+
+    // Run still water over the bottom `b` between two walls, and return the largest change
+    // in the depth and the largest discharge.
+    fn lake_at_rest_errors<Fb>(surface: f64, b: Fb) -> (f64, f64)
+    where
+        Fb: Fn(f64) -> f64 + Copy,
+    {
+        let n_p = 3;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let mut depth_error: f64 = 0.;
+        let mut discharge: f64 = 0.;
+        for &solver in SOLVERS.iter() {
+            let grid = grid(20, &reference_element, solver, reflective_boundary, b);
+            let operators = assemble_operators::<ShallowWaterState>(&reference_element);
+            let storages = shallow_water_1d(
+                &|xs: &Vector<f64>| lake_at_rest(xs, surface, b),
+                &grid,
+                &reference_element,
+                &operators,
+                0.5,
+                &mut SSPRK3 {},
+                Some(SlopeLimiter::Minmod),
+            );
+            for (elt, storage) in grid.elements.iter().zip(storages.iter()) {
+                let q = &storage.u_k;
+                for i in 0..q.h.size() {
+                    let h = (surface - elt.spatial_flux.b[i]).max(0.);
+                    let error = (q.h[i] - h).abs();
+                    if error.is_nan() || error > depth_error {
+                        depth_error = error;
+                    }
+                    if q.hu[i].is_nan() || q.hu[i].abs() > discharge {
+                        discharge = q.hu[i].abs();
+                    }
+                }
+            }
+        }
+        (depth_error, discharge)
+    }
+
+    #[test]
+    fn test_lake_at_rest_over_hump() {
+        let (depth_error, discharge) = lake_at_rest_errors(1., hump);
+        assert!(depth_error < 1e-12);
+        assert!(discharge < 1e-12);
+    }
+
+    #[test]
+    fn test_lake_at_rest_over_step() {
+        // The step lies on an element boundary, so the bottom is discontinuous there.
+        let (depth_error, discharge) =
+            lake_at_rest_errors(1., |x| if x < 0.3 { 0. } else { 0.4 });
+        assert!(depth_error < 1e-12);
+        assert!(discharge < 1e-12);
+    }
+
+    #[test]
+    fn test_lake_at_rest_around_island() {
+        // The top of the hump sticks out of the water.
+        let (depth_error, discharge) = lake_at_rest_errors(0.4, hump);
+        assert!(depth_error < 1e-12);
+        assert!(discharge < 1e-12);
+    }
+
+    // SSPRK3, recording the length of each step along with the fastest signal speed at its
+    // start.
+    struct RecordingStepper {
+        steps: Vec<(f64, f64)>,
+    }
+
+    impl TimeStepper<ShallowWaterState> for RecordingStepper {
+        fn step<GS, RHS, Lim>(
+            &mut self,
+            t: f64,
+            dt: f64,
+            grid: &grid::Grid<GS>,
+            storages: &mut Vec<grid::ElementStorage<ShallowWaterState, GS::F>>,
+            rhs: &RHS,
+            limit: &Lim,
+        ) where
+            GS: GalerkinScheme<U = ShallowWaterState>,
+            RHS: SpatialOperator<GS>,
+            Lim: Fn(&grid::Grid<GS>, &mut Vec<grid::ElementStorage<ShallowWaterState, GS::F>>),
+        {
+            let speed = storages
+                .iter()
+                .flat_map(|storage| {
+                    let u_k = &storage.u_k;
+                    u_k.h
+                        .iter()
+                        .zip(u_k.hu.iter())
+                        .map(|(&h, &hu)| {
+                            let q = ShallowWaterUnit { h, hu };
+                            q.velocity().abs() + 2. * q.celerity()
+                        })
+                        .collect::<Vec<f64>>()
+                })
+                .fold(0., f64::max);
+            self.steps.push((dt, speed));
+            SSPRK3 {}.step(t, dt, grid, storages, rhs, limit);
+        }
+    }
+
+    #[test]
+    fn test_time_step_follows_accelerating_flow() {
+        // A thin film on the bed b = -x slides downhill with u = g t, so by t = 0.5 its
+        // signal speed has grown almost tenfold from 2 sqrt(g h).
+        let n_p = 2;
+        let reference_element = ReferenceElement::legendre(n_p);
+        let grid = grid(
+            40,
+            &reference_element,
+            RiemannSolver::HLL,
+            transmissive_boundary,
+            |x| -x,
+        );
+        let operators = assemble_operators::<ShallowWaterState>(&reference_element);
+        let h_0 = 0.01;
+        let final_time = 0.5;
+        let mut stepper = RecordingStepper { steps: vec![] };
+        let storages = shallow_water_1d(
+            &|xs: &Vector<f64>| ShallowWaterState {
+                h: Vector::ones(xs.size()) * h_0,
+                hu: Vector::zeros(xs.size()),
+            },
+            &grid,
+            &reference_element,
+            &operators,
+            final_time,
+            &mut stepper,
+            None,
+        );
+
+        let (_, first_speed) = stepper.steps[0];
+        let (_, last_speed) = stepper.steps[stepper.steps.len() - 1];
+        assert!(last_speed > 5. * first_speed);
+        for &(dt, speed) in stepper.steps.iter() {
+            assert!(dt <= cfl_time_step(&grid, &reference_element, speed, 0.2) * (1. + 1e-12));
+        }
+        assert!((stepper.steps.iter().map(|&(dt, _)| dt).sum::<f64>() - final_time).abs() < 1e-12);
+
+        // The film stays uniform, and its discharge grows linearly in time.
+        for storage in storages.iter() {
+            for (&h, &hu) in storage.u_k.h.iter().zip(storage.u_k.hu.iter()) {
+                assert!((h - h_0).abs() < 1e-10);
+                assert!((hu - h_0 * GRAVITY * final_time).abs() < 1e-8);
+            }
+        }
+    }
+
+    // Ritter's solution for the dam break onto a dry bed.
+    fn ritter_depth(x: f64, t: f64) -> f64 {
+        let c = GRAVITY.sqrt();
+        if x <= -c * t {
+            1.
+        } else if x < 2. * c * t {
+            (2. * c - x / t).powi(2) / (9. * GRAVITY)
+        } else {
+            0.
+        }
+    }
+
+    #[test]
+    fn test_dry_dam_break() {
+        let n_p = 2;
+        let reference_element = ReferenceElement::legendre(n_p);
+        for &solver in [RiemannSolver::Rusanov, RiemannSolver::HLL].iter() {
+            let grid = grid(100, &reference_element, solver, transmissive_boundary, |_| 0.);
+            let operators = assemble_operators::<ShallowWaterState>(&reference_element);
+            let final_time = 0.1;
+            let storages = shallow_water_1d(
+                &dry_dam_break,
+                &grid,
+                &reference_element,
+                &operators,
+                final_time,
+                &mut SSPRK3 {},
+                Some(SlopeLimiter::Minmod),
+            );
+
+            // The depth stays non-negative, and converges to Ritter's solution at first order
+            // because of the kink at the front.
+            let weights = &operators.mass * Vector::ones(n_p as usize + 1);
+            let mut l1_error = 0.;
+            for (elt, storage) in grid.elements.iter().zip(storages.iter()) {
+                let h = &storage.u_k.h;
+                assert!(h.iter().all(|&h| h >= 0.));
+                let jacobian = (elt.x_right - elt.x_left) / 2.;
+                for i in 0..h.size() {
+                    let error = (h[i] - ritter_depth(elt.x_k[i], final_time)).abs();
+                    l1_error += jacobian * weights[i] * error;
+                }
+            }
+            assert!(l1_error < 2e-2);
+        }
+    }
+
+    #[test]
+    fn test_wet_dam_break() {
+        // With limiting, the depth should stay between its initial extremes.
+        let n_p = 2;
+        let reference_element = ReferenceElement::legendre(n_p);
+        for &solver in SOLVERS.iter() {
+            let grid = grid(100, &reference_element, solver, transmissive_boundary, |_| 0.);
+            let operators = assemble_operators::<ShallowWaterState>(&reference_element);
+            let storages = shallow_water_1d(
+                &wet_dam_break,
+                &grid,
+                &reference_element,
+                &operators,
+                0.1,
+                &mut SSPRK3 {},
+                Some(SlopeLimiter::Minmod),
+            );
+            for storage in storages.iter() {
+                for h in storage.u_k.h.iter() {
+                    assert!(*h > 0.5 - 1e-2 && *h < 1. + 1e-2);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use functions::range_kutta::{RKA, RKB, RKC};
+use galerkin_1d::galerkin::GalerkinScheme;
+use galerkin_1d::grid::{Element, ElementStorage, Grid, SpatialFlux};
+use galerkin_1d::unknowns::{communicate, Unknown};
+
+// The DG spatial operator L(u, t) of the semi-discrete system du/dt = L(u, t).
+//
+// Any closure |elt, elt_storage, t| -> U evaluating L element by element is an operator;
+// face values are communicated before it is called, and the scheme's source term is added
+// to what it returns. Operators which need more than one pass over the grid, such as the
+// LDG discretization of second derivatives, implement this trait directly.
+pub trait SpatialOperator<GS: GalerkinScheme> {
+    // Evaluate L(u, t) on every element.
+    fn evaluate(
+        &self,
+        t: f64,
+        grid: &Grid<GS>,
+        storages: &Vec<ElementStorage<GS::U, GS::F>>,
+    ) -> Vec<GS::U>;
+}
+
+impl<GS, RHS> SpatialOperator<GS> for RHS
+where
+    GS: GalerkinScheme,
+    RHS: Fn(&Element<GS>, &ElementStorage<GS::U, GS::F>, f64) -> GS::U,
+{
+    fn evaluate(
+        &self,
+        t: f64,
+        grid: &Grid<GS>,
+        storages: &Vec<ElementStorage<GS::U, GS::F>>,
+    ) -> Vec<GS::U> {
+        communicate(t, grid, storages);
+        grid.elements
+            .iter()
+            .map(|elt| {
+                let storage = &storages[elt.index as usize];
+                let rhs = self(elt, storage, t);
+                match GS::source(&elt.x_k, t, &storage.u_k) {
+                    Some(source) => rhs + source,
+                    None => rhs,
+                }
+            })
+            .collect()
+    }
+}
+
+// An explicit one-step method for the semi-discrete system du/dt = L(u, t), where L is the
+// DG spatial operator `rhs`.
+pub trait TimeStepper<U: Unknown> {
+    // Advance the solution in `storages` from time t to t + dt, calling `limit` on the
+    // solution after every stage.
+    fn step<GS, RHS, Lim>(
+        &mut self,
+        t: f64,
+        dt: f64,
+        grid: &Grid<GS>,
+        storages: &mut Vec<ElementStorage<U, GS::F>>,
+        rhs: &RHS,
+        limit: &Lim,
+    ) where
+        GS: GalerkinScheme<U = U>,
+        RHS: SpatialOperator<GS>,
+        Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<U, GS::F>>);
+
+    // The registers carried over from one step to the next, one per element. They must be
+    // saved along with the solution to resume a run; most methods keep none.
+    fn residuals(&self) -> &[U] {
+        &[]
+    }
+
+    fn set_residuals(&mut self, _residuals: Vec<U>) {}
+}
+
+// The five-stage, fourth-order low-storage Runge-Kutta method of Carpenter and Kennedy.
+// Only one extra register of residuals is kept per element, which makes it the method of
+// choice for linear wave problems.
+pub struct LowStorageRK45<U: Unknown> {
+    pub residuals: Vec<U>,
+}
+
+impl<U: Unknown> LowStorageRK45<U> {
+    pub fn new() -> LowStorageRK45<U> {
+        LowStorageRK45 { residuals: vec![] }
+    }
+}
+
+impl<U: Unknown> TimeStepper<U> for LowStorageRK45<U> {
+    fn step<GS, RHS, Lim>(
+        &mut self,
+        t: f64,
+        dt: f64,
+        grid: &Grid<GS>,
+        storages: &mut Vec<ElementStorage<U, GS::F>>,
+        rhs: &RHS,
+        limit: &Lim,
+    ) where
+        GS: GalerkinScheme<U = U>,
+        RHS: SpatialOperator<GS>,
+        Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<U, GS::F>>),
+    {
+        if self.residuals.len() != storages.len() {
+            self.residuals = storages
+                .iter()
+                .map(|storage| storage.u_k.clone() * 0.)
+                .collect();
+        }
+
+        for int_rk in 0..5 {
+            let t = t + RKC[int_rk] * dt;
+
+            let l = rhs.evaluate(t, grid, storages);
+
+            // update each element's local solution
+            for (k, (storage, rhs_u)) in storages.iter_mut().zip(l.into_iter()).enumerate() {
+                let residuals_u = self.residuals[k].clone() * RKA[int_rk] + rhs_u * dt;
+                storage.u_k = storage.u_k.clone() + residuals_u.clone() * RKB[int_rk];
+                self.residuals[k] = residuals_u;
+            }
+
+            limit(grid, storages);
+        }
+    }
+
+    fn residuals(&self) -> &[U] {
+        &self.residuals
+    }
+
+    fn set_residuals(&mut self, residuals: Vec<U>) {
+        self.residuals = residuals;
+    }
+}
+
+// The two-stage, second-order strong-stability-preserving method (Heun's method), written
+// as a convex combination of forward Euler steps.
+pub struct SSPRK2 {}
+
+impl<U: Unknown> TimeStepper<U> for SSPRK2 {
+    fn step<GS, RHS, Lim>(
+        &mut self,
+        t: f64,
+        dt: f64,
+        grid: &Grid<GS>,
+        storages: &mut Vec<ElementStorage<U, GS::F>>,
+        rhs: &RHS,
+        limit: &Lim,
+    ) where
+        GS: GalerkinScheme<U = U>,
+        RHS: SpatialOperator<GS>,
+        Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<U, GS::F>>),
+    {
+        let u_n = current_solution(storages);
+
+        let l = rhs.evaluate(t, grid, storages);
+        update(storages, |k, u| u + l[k].clone() * dt);
+        limit(grid, storages);
+
+        let l = rhs.evaluate(t + dt, grid, storages);
+        update(storages, |k, u| u_n[k].clone() * 0.5 + (u + l[k].clone() * dt) * 0.5);
+        limit(grid, storages);
+    }
+}
+
+// The three-stage, third-order strong-stability-preserving method of Shu and Osher.
+pub struct SSPRK3 {}
+
+impl<U: Unknown> TimeStepper<U> for SSPRK3 {
+    fn step<GS, RHS, Lim>(
+        &mut self,
+        t: f64,
+        dt: f64,
+        grid: &Grid<GS>,
+        storages: &mut Vec<ElementStorage<U, GS::F>>,
+        rhs: &RHS,
+        limit: &Lim,
+    ) where
+        GS: GalerkinScheme<U = U>,
+        RHS: SpatialOperator<GS>,
+        Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<U, GS::F>>),
+    {
+        let u_n = current_solution(storages);
+
+        let l = rhs.evaluate(t, grid, storages);
+        update(storages, |k, u| u + l[k].clone() * dt);
+        limit(grid, storages);
+
+        let l = rhs.evaluate(t + dt, grid, storages);
+        update(storages, |k, u| u_n[k].clone() * 0.75 + (u + l[k].clone() * dt) * 0.25);
+        limit(grid, storages);
+
+        let l = rhs.evaluate(t + 0.5 * dt, grid, storages);
+        update(storages, |k, u| {
+            u_n[k].clone() * (1. / 3.) + (u + l[k].clone() * dt) * (2. / 3.)
+        });
+        limit(grid, storages);
+    }
+}
+
+// The classical four-stage, fourth-order Runge-Kutta method.
+pub struct ClassicalRK4 {}
+
+impl<U: Unknown> TimeStepper<U> for ClassicalRK4 {
+    fn step<GS, RHS, Lim>(
+        &mut self,
+        t: f64,
+        dt: f64,
+        grid: &Grid<GS>,
+        storages: &mut Vec<ElementStorage<U, GS::F>>,
+        rhs: &RHS,
+        limit: &Lim,
+    ) where
+        GS: GalerkinScheme<U = U>,
+        RHS: SpatialOperator<GS>,
+        Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<U, GS::F>>),
+    {
+        let u_n = current_solution(storages);
+
+        let k1 = rhs.evaluate(t, grid, storages);
+        update(storages, |k, _| u_n[k].clone() + k1[k].clone() * (0.5 * dt));
+        limit(grid, storages);
+
+        let k2 = rhs.evaluate(t + 0.5 * dt, grid, storages);
+        update(storages, |k, _| u_n[k].clone() + k2[k].clone() * (0.5 * dt));
+        limit(grid, storages);
+
+        let k3 = rhs.evaluate(t + 0.5 * dt, grid, storages);
+        update(storages, |k, _| u_n[k].clone() + k3[k].clone() * dt);
+        limit(grid, storages);
+
+        let k4 = rhs.evaluate(t + dt, grid, storages);
+        update(storages, |k, _| {
+            let sum = k1[k].clone() + k2[k].clone() * 2. + k3[k].clone() * 2. + k4[k].clone();
+            u_n[k].clone() + sum * (dt / 6.)
+        });
+        limit(grid, storages);
+    }
+}
+
+fn current_solution<U, F>(storages: &Vec<ElementStorage<U, F>>) -> Vec<U>
+where
+    U: Unknown,
+    F: SpatialFlux,
+{
+    storages.iter().map(|storage| storage.u_k.clone()).collect()
+}
+
+// Replace the solution on element k with `f(k, u_k)`.
+fn update<U, F, Fu>(storages: &mut Vec<ElementStorage<U, F>>, f: Fu)
+where
+    U: Unknown,
+    F: SpatialFlux,
+    Fu: Fn(usize, U) -> U,
+{
+    for (k, storage) in storages.iter_mut().enumerate() {
+        storage.u_k = f(k, storage.u_k.clone());
+    }
+}
+
+// Advance the solution in `storages` from t = 0 to `final_time` with the given time stepper.
+//
+// `dt` is the largest acceptable step; it is shrunk so that a whole number of steps lands
+// exactly on `final_time`. Before every stage the face values are communicated, and then
+// `rhs` is evaluated on each element at the stage time. `after_step` is called at the end
+// of every step with the step number and the time reached, and may modify the solution.
+pub fn integrate<GS, TS, RHS, Cb>(
+    final_time: f64,
+    dt: f64,
+    grid: &Grid<GS>,
+    storages: &mut Vec<ElementStorage<GS::U, GS::F>>,
+    stepper: &mut TS,
+    rhs: RHS,
+    after_step: Cb,
+) where
+    GS: GalerkinScheme,
+    TS: TimeStepper<GS::U>,
+    RHS: Fn(&Element<GS>, &ElementStorage<GS::U, GS::F>, f64) -> GS::U,
+    Cb: FnMut(i32, f64, &mut Vec<ElementStorage<GS::U, GS::F>>),
+{
+    integrate_with_limiter(
+        final_time,
+        dt,
+        grid,
+        storages,
+        stepper,
+        rhs,
+        |_, _| {},
+        after_step,
+    );
+}
+
+// As `integrate`, but `limit` is applied to the solution after every Runge-Kutta stage.
+pub fn integrate_with_limiter<GS, TS, RHS, Lim, Cb>(
+    final_time: f64,
+    dt: f64,
+    grid: &Grid<GS>,
+    storages: &mut Vec<ElementStorage<GS::U, GS::F>>,
+    stepper: &mut TS,
+    rhs: RHS,
+    limit: Lim,
+    after_step: Cb,
+) where
+    GS: GalerkinScheme,
+    TS: TimeStepper<GS::U>,
+    RHS: Fn(&Element<GS>, &ElementStorage<GS::U, GS::F>, f64) -> GS::U,
+    Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<GS::U, GS::F>>),
+    Cb: FnMut(i32, f64, &mut Vec<ElementStorage<GS::U, GS::F>>),
+{
+    integrate_operator(final_time, dt, grid, storages, stepper, &rhs, limit, after_step);
+}
+
+// As `integrate_with_limiter`, for problems whose wave speeds change as the solution
+// evolves. Before every step `time_step` picks the step from the current solution, and the
+// last step is shortened so that it lands exactly on `final_time`.
+pub fn integrate_adaptive<GS, TS, RHS, Dt, Lim, Cb>(
+    final_time: f64,
+    grid: &Grid<GS>,
+    storages: &mut Vec<ElementStorage<GS::U, GS::F>>,
+    stepper: &mut TS,
+    rhs: RHS,
+    time_step: Dt,
+    limit: Lim,
+    mut after_step: Cb,
+) where
+    GS: GalerkinScheme,
+    TS: TimeStepper<GS::U>,
+    RHS: Fn(&Element<GS>, &ElementStorage<GS::U, GS::F>, f64) -> GS::U,
+    Dt: Fn(&Vec<ElementStorage<GS::U, GS::F>>) -> f64,
+    Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<GS::U, GS::F>>),
+    Cb: FnMut(i32, f64, &mut Vec<ElementStorage<GS::U, GS::F>>),
+{
+    let mut t: f64 = 0.;
+    let mut epoch = 0;
+    while t < final_time {
+        let dt = time_step(storages);
+        assert!(dt > 0., "the time step must be positive");
+        let last = t + dt >= final_time;
+        let dt = if last { final_time - t } else { dt };
+        stepper.step(t, dt, grid, storages, &rhs, &limit);
+        t = if last { final_time } else { t + dt };
+        after_step(epoch, t, storages);
+        epoch = epoch + 1;
+    }
+}
+
+// As `integrate_with_limiter`, for spatial operators which are not element-by-element
+// closures.
+pub fn integrate_operator<GS, TS, Op, Lim, Cb>(
+    final_time: f64,
+    dt: f64,
+    grid: &Grid<GS>,
+    storages: &mut Vec<ElementStorage<GS::U, GS::F>>,
+    stepper: &mut TS,
+    operator: &Op,
+    limit: Lim,
+    mut after_step: Cb,
+) where
+    GS: GalerkinScheme,
+    TS: TimeStepper<GS::U>,
+    Op: SpatialOperator<GS>,
+    Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<GS::U, GS::F>>),
+    Cb: FnMut(i32, f64, &mut Vec<ElementStorage<GS::U, GS::F>>),
+{
+    resume_operator(
+        0,
+        0.,
+        final_time,
+        dt,
+        grid,
+        storages,
+        stepper,
+        operator,
+        limit,
+        |epoch, t, storages, _| after_step(epoch, t, storages),
+    );
+}
+
+// As `integrate_operator`, but starting from step `start_step` at time `start_time`, as
+// recorded in a checkpoint of an earlier run to the same `final_time` with the same `dt`.
+// Given the solution and the stepper's residuals from that checkpoint, the remaining steps
+// are the same, bit for bit, as those of the uninterrupted run. `after_step` is also passed
+// the stepper, so that it can write checkpoints of its own.
+pub fn resume_operator<GS, TS, Op, Lim, Cb>(
+    start_step: i32,
+    start_time: f64,
+    final_time: f64,
+    dt: f64,
+    grid: &Grid<GS>,
+    storages: &mut Vec<ElementStorage<GS::U, GS::F>>,
+    stepper: &mut TS,
+    operator: &Op,
+    limit: Lim,
+    mut after_step: Cb,
+) where
+    GS: GalerkinScheme,
+    TS: TimeStepper<GS::U>,
+    Op: SpatialOperator<GS>,
+    Lim: Fn(&Grid<GS>, &mut Vec<ElementStorage<GS::U, GS::F>>),
+    Cb: FnMut(i32, f64, &mut Vec<ElementStorage<GS::U, GS::F>>, &TS),
+{
+    let (n_t, dt) = time_steps(final_time, dt);
+
+    let mut t: f64 = start_time;
+
+    for epoch in start_step..n_t {
+        stepper.step(t, dt, grid, storages, operator, &limit);
+        t = t + dt;
+        after_step(epoch, t, storages, stepper);
+    }
+}
+
+// The number of steps to `final_time`, and their length: `dt` shrunk so that a whole number
+// of steps lands exactly on `final_time`.
+pub fn time_steps(final_time: f64, dt: f64) -> (i32, f64) {
+    let n_t = (final_time / dt).ceil() as i32;
+    (n_t, final_time / n_t as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::{integrate, ClassicalRK4, LowStorageRK45, SSPRK2, SSPRK3, TimeStepper};
+    use galerkin_1d::flux::{FluxEnum, FluxScheme, FreeflowFlux};
+    use galerkin_1d::galerkin::{Formulation, GalerkinScheme};
+    use galerkin_1d::grid::{freeFlowBoundary, generate_grid, Face, ReferenceElement};
+    use galerkin_1d::operators::assemble_operators;
+    use galerkin_1d::unknowns::{initialize_storage, Unknown};
+    use rulinalg::vector::Vector;
+    use std::ops::{Add, Mul};
+
+    #[derive(Debug, Clone)]
+    struct Decay {
+        u: Vector<f64>,
+    }
+
+    impl Add for Decay {
+        type Output = Decay;
+
+        fn add(self, other: Decay) -> Decay {
+            Decay { u: self.u + other.u }
+        }
+    }
+
+    impl Mul<f64> for Decay {
+        type Output = Decay;
+
+        fn mul(self, other: f64) -> Decay {
+            Decay { u: self.u * other }
+        }
+    }
+
+    impl Unknown for Decay {
+        type Unit = f64;
+
+        fn first(&self) -> f64 {
+            self.u[0]
+        }
+
+        fn last(&self) -> f64 {
+            self.u[self.u.size() - 1]
+        }
+
+        fn zero() -> f64 {
+            0.
+        }
+    }
+
+    struct DecayFluxScheme {}
+
+    impl FluxScheme<Decay, f64> for DecayFluxScheme {
+        type Left = FreeflowFlux;
+        type Right = FreeflowFlux;
+        type Interior = FreeflowFlux;
+    }
+
+    struct DecayScheme {}
+
+    impl GalerkinScheme for DecayScheme {
+        type U = Decay;
+        type F = f64;
+        type FS = DecayFluxScheme;
+
+        const FORMULATION: Formulation = Formulation::Strong;
+    }
+
+    // Integrates du/dt = -u to t = 1 and returns the error at the nodes.
+    fn decay_error<TS: TimeStepper<Decay>>(stepper: &mut TS, dt: f64) -> f64 {
+        let reference_element = ReferenceElement::legendre(2);
+        let operators = assemble_operators::<Decay>(&reference_element);
+        let grid = generate_grid::<DecayScheme, _>(
+            0.,
+            1.,
+            2,
+            &reference_element,
+            Face {
+                face_type: freeFlowBoundary(0.),
+                flux: FluxEnum::Left(FreeflowFlux {}),
+            },
+            Face {
+                face_type: freeFlowBoundary(0.),
+                flux: FluxEnum::Right(FreeflowFlux {}),
+            },
+            FreeflowFlux {},
+            |_| 0.,
+        );
+        let mut storages = initialize_storage(
+            |xs: &Vector<f64>| Decay {
+                u: Vector::ones(xs.size()),
+            },
+            reference_element.n_p,
+            &grid,
+            &operators,
+        );
+        integrate(
+            1.,
+            dt,
+            &grid,
+            &mut storages,
+            stepper,
+            |_, storage, _| storage.u_k.clone() * -1.,
+            |_, _, _| {},
+        );
+        (storages[0].u_k.u[0] - (-1.0_f64).exp()).abs()
+    }
+
+    fn observed_order<TS: TimeStepper<Decay>>(stepper: &mut TS) -> f64 {
+        let coarse = decay_error(stepper, 0.1);
+        let fine = decay_error(stepper, 0.05);
+        (coarse / fine).log2()
+    }
+
+    #[test]
+    fn test_ssp_rk2_order() {
+        assert!((observed_order(&mut SSPRK2 {}) - 2.).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_ssp_rk3_order() {
+        assert!((observed_order(&mut SSPRK3 {}) - 3.).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_classical_rk4_order() {
+        assert!((observed_order(&mut ClassicalRK4 {}) - 4.).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_low_storage_rk45_order() {
+        assert!((observed_order(&mut LowStorageRK45::new()) - 4.).abs() < 0.2);
+    }
+}
@@ -2,6 +2,7 @@ extern crate core;
 extern crate rulinalg;
 
 use self::core::ops::{Add, Div, Mul, Neg};
+use self::rulinalg::matrix::Matrix;
 use self::rulinalg::vector::Vector;
 use galerkin_1d::galerkin::GalerkinScheme;
 use galerkin_1d::grid;
@@ -11,7 +12,9 @@ use galerkin_1d::operators::Operators;
 use std::cell::Cell;
 use std::fmt;
 
-pub trait Unknown {
+// The solution on a single element. Time integrators form linear combinations of these, so
+// the unknown must support addition and scaling.
+pub trait Unknown: Clone + Add<Output = Self> + Mul<f64, Output = Self> {
     type Unit: Neg<Output = Self::Unit>
         + Add<Output = Self::Unit>
         + Mul<f64, Output = Self::Unit>
@@ -26,6 +29,31 @@ pub trait Unknown {
     fn zero() -> Self::Unit;
 }
 
+// An unknown made up of scalar fields that can be operated on one at a time, for example by
+// limiters and filters.
+pub trait Components: Unknown {
+    fn components(&self) -> Vec<&Vector<f64>>;
+
+    fn from_components(components: Vec<Vector<f64>>) -> Self;
+}
+
+// Apply a nodal operator such as a modal filter to every component of the solution.
+pub fn apply_filter<U, F>(filter: &Matrix<f64>, storages: &mut Vec<ElementStorage<U, F>>)
+where
+    U: Components,
+    F: SpatialFlux,
+{
+    for storage in storages.iter_mut() {
+        let filtered = storage
+            .u_k
+            .components()
+            .into_iter()
+            .map(|component| filter * component)
+            .collect();
+        storage.u_k = U::from_components(filtered);
+    }
+}
+
 pub fn initialize_storage<GS, Fx>(
     u_0: Fx,
     n_p: i32,
@@ -108,7 +136,7 @@ pub fn communicate<GS: GalerkinScheme>(
                 (last, u_k_plus_1.first())
             }
             grid::FaceType::Boundary(ref b, _) => {
-                let bc = b(t, first);
+                let bc = b(t, last);
                 (last, bc)
             }
         };
@@ -116,3 +144,106 @@ pub fn communicate<GS: GalerkinScheme>(
         storage.u_right_plus.set(u_right_plus);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::{communicate, initialize_storage, Unknown};
+    use galerkin_1d::flux::{FluxEnum, FluxScheme, FreeflowFlux};
+    use galerkin_1d::galerkin::{Formulation, GalerkinScheme};
+    use galerkin_1d::grid::{freeFlowBoundary, generate_grid, Face, ReferenceElement};
+    use galerkin_1d::operators::assemble_operators;
+    use rulinalg::vector::Vector;
+    use std::ops::{Add, Mul};
+
+    #[derive(Debug, Clone)]
+    struct Field {
+        u: Vector<f64>,
+    }
+
+    impl Add for Field {
+        type Output = Field;
+
+        fn add(self, other: Field) -> Field {
+            Field { u: self.u + other.u }
+        }
+    }
+
+    impl Mul<f64> for Field {
+        type Output = Field;
+
+        fn mul(self, other: f64) -> Field {
+            Field { u: self.u * other }
+        }
+    }
+
+    impl Unknown for Field {
+        type Unit = f64;
+
+        fn first(&self) -> f64 {
+            self.u[0]
+        }
+
+        fn last(&self) -> f64 {
+            self.u[self.u.size() - 1]
+        }
+
+        fn zero() -> f64 {
+            0.
+        }
+    }
+
+    struct FieldFluxScheme {}
+
+    impl FluxScheme<Field, f64> for FieldFluxScheme {
+        type Left = FreeflowFlux;
+        type Right = FreeflowFlux;
+        type Interior = FreeflowFlux;
+    }
+
+    struct FieldScheme {}
+
+    impl GalerkinScheme for FieldScheme {
+        type U = Field;
+        type F = f64;
+        type FS = FieldFluxScheme;
+
+        const FORMULATION: Formulation = Formulation::Strong;
+    }
+
+    #[test]
+    fn test_boundaries_mirror_their_own_face() {
+        // With u = x and boundaries which mirror the interior value, the exterior value on
+        // each boundary face is the value at that end of the domain.
+        let reference_element = ReferenceElement::legendre(2);
+        let operators = assemble_operators::<Field>(&reference_element);
+        let grid = generate_grid::<FieldScheme, _>(
+            0.,
+            1.,
+            2,
+            &reference_element,
+            Face {
+                face_type: freeFlowBoundary(0.),
+                flux: FluxEnum::Left(FreeflowFlux {}),
+            },
+            Face {
+                face_type: freeFlowBoundary(0.),
+                flux: FluxEnum::Right(FreeflowFlux {}),
+            },
+            FreeflowFlux {},
+            |_| 0.,
+        );
+        let storages = initialize_storage(
+            |xs: &Vector<f64>| Field { u: xs.clone() },
+            reference_element.n_p,
+            &grid,
+            &operators,
+        );
+        communicate(0., &grid, &storages);
+
+        assert_eq!(storages[0].u_left_plus.get(), storages[0].u_k.first());
+        assert_eq!(storages[1].u_right_plus.get(), storages[1].u_k.last());
+        assert!((storages[1].u_right_plus.get() - 1.).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,71 @@
+use checkpoint::file::Checkpoint;
+use galerkin_2d::galerkin::GalerkinScheme;
+use galerkin_2d::grid::ElementStorage;
+use galerkin_2d::unknowns::Components;
+use rulinalg::vector::Vector;
+use std::io;
+
+// The state of a run of order `order` after `step` steps of length `dt`, at time t, where
+// `residuals` are the registers of the low-storage Runge-Kutta method.
+pub fn capture<GS>(
+    order: i32,
+    step: i32,
+    time: f64,
+    dt: f64,
+    storages: &Vec<ElementStorage<GS>>,
+    residuals: &Vec<GS::U>,
+) -> Checkpoint
+where
+    GS: GalerkinScheme,
+    GS::U: Components,
+{
+    Checkpoint {
+        order,
+        step,
+        time,
+        dt,
+        solution: storages.iter().map(|storage| fields(&storage.u_k)).collect(),
+        residuals: residuals.iter().map(fields).collect(),
+    }
+}
+
+// Put the solution and the residuals back the way they were when `checkpoint` was captured,
+// and return the step and the time to resume from. The grid must be the one the checkpoint
+// was taken on, and the steps must be of the same length `dt`. A checkpoint of another order
+// or step, with differently shaped elements or without residuals is rejected as invalid data,
+// leaving the solution alone.
+pub fn restore<GS>(
+    checkpoint: &Checkpoint,
+    order: i32,
+    dt: f64,
+    storages: &mut Vec<ElementStorage<GS>>,
+    residuals: &mut Vec<GS::U>,
+) -> io::Result<(i32, f64)>
+where
+    GS: GalerkinScheme,
+    GS::U: Components,
+{
+    let solution: Vec<Vec<Vector<f64>>> =
+        storages.iter().map(|storage| fields(&storage.u_k)).collect();
+    checkpoint.check_layout(order, &solution)?;
+    checkpoint.check_time_step(dt)?;
+    if checkpoint.residuals.len() != residuals.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the checkpoint has no residuals",
+        ));
+    }
+
+    for (storage, components) in storages.iter_mut().zip(checkpoint.solution.iter()) {
+        storage.u_k = GS::U::from_components(components.clone());
+    }
+    for (residual, components) in residuals.iter_mut().zip(checkpoint.residuals.iter()) {
+        *residual = GS::U::from_components(components.clone());
+    }
+
+    Ok((checkpoint.step, checkpoint.time))
+}
+
+fn fields<U: Components>(u: &U) -> Vec<Vector<f64>> {
+    u.components().into_iter().cloned().collect()
+}
@@ -0,0 +1,99 @@
+use convergence::table::ConvergenceTable;
+use distmesh::mesh::Mesh;
+use galerkin_2d::norms::ErrorNorms;
+use galerkin_2d::operators::{assemble_operators, Operators};
+use galerkin_2d::reference_element::ReferenceElement;
+
+// Run `solve` for every polynomial order in `orders` on `mesh` and on `refinements`
+// successive uniform refinements of it, and tabulate the errors it returns with the observed
+// rates. The element size h is the longest edge of each mesh.
+pub fn convergence_study<F>(
+    orders: &[i32],
+    mesh: Mesh,
+    refinements: i32,
+    mut solve: F,
+) -> ConvergenceTable
+where
+    F: FnMut(&ReferenceElement, &Operators, &Mesh) -> ErrorNorms,
+{
+    let mut meshes = vec![mesh];
+    for _ in 0..refinements {
+        let refined = meshes[meshes.len() - 1].refine();
+        meshes.push(refined);
+    }
+
+    let mut table = ConvergenceTable::new();
+    for &n in orders.iter() {
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        for mesh in meshes.iter() {
+            let norms = solve(&reference_element, &operators, mesh);
+            table.push(
+                n,
+                mesh.triangles.len(),
+                mesh.max_edge_length(),
+                norms.l2,
+                norms.l_inf,
+                norms.h1_seminorm,
+            );
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::convergence_study;
+    use distmesh::distmesh_2d::unit_square;
+    use galerkin_2d::norms::ErrorNorms;
+
+    #[test]
+    fn test_convergence_study() {
+        // Errors which scale exactly as h^(N + 1), and as h^N for the gradient, so the
+        // observed rates are exact.
+        let mesh = unit_square();
+        let (n_k, h) = (mesh.triangles.len(), mesh.max_edge_length());
+        let mut runs = vec![];
+        let table = convergence_study(&[1, 2], mesh, 2, |reference_element, _, mesh| {
+            let n = reference_element.n;
+            let h = mesh.max_edge_length();
+            runs.push((n, mesh.triangles.len()));
+            ErrorNorms {
+                l2: h.powi(n + 1),
+                l_inf: 3. * h.powi(n + 1),
+                h1_seminorm: 2. * h.powi(n),
+            }
+        });
+
+        // Every refinement of the mesh is solved at the first order before the next order.
+        let expected = vec![
+            (1, n_k),
+            (1, 4 * n_k),
+            (1, 16 * n_k),
+            (2, n_k),
+            (2, 4 * n_k),
+            (2, 16 * n_k),
+        ];
+        assert_eq!(runs, expected);
+        let rows: Vec<(i32, usize)> = table.rows.iter().map(|row| (row.n, row.n_k)).collect();
+        assert_eq!(rows, expected);
+
+        for &n in [1, 2].iter() {
+            let rows = table.order(n);
+            assert!(rows[0].l2_rate.is_none());
+            for (i, row) in rows.iter().enumerate() {
+                // Each refinement halves the longest edge.
+                assert!((row.h - h / 2f64.powi(i as i32)).abs() < 1e-12);
+                assert!((row.l2 - row.h.powi(n + 1)).abs() < 1e-12);
+            }
+            let is_rate = |rate: Option<f64>, expected: i32| {
+                (rate.expect("not the coarsest run") - expected as f64).abs() < 1e-10
+            };
+            for row in rows[1..].iter() {
+                assert!(is_rate(row.l2_rate, n + 1));
+                assert!(is_rate(row.l_inf_rate, n + 1));
+                assert!(is_rate(row.h1_seminorm_rate, n));
+            }
+        }
+    }
+}
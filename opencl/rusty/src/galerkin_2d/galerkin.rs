@@ -3,9 +3,16 @@ extern crate rulinalg;
 use galerkin_2d::flux::FluxScheme;
 use galerkin_2d::operators::FaceLiftable;
 use galerkin_2d::unknowns::Unknown;
+use rulinalg::vector::Vector;
 use std::fmt::Debug;
 
 pub trait GalerkinScheme {
     type U: Unknown + FaceLiftable + Debug;
     type FS: FluxScheme<Self::U>;
+
+    // The source term S(x, y, t, u) on the right-hand side, evaluated at the nodes `xs`, `ys`
+    // of an element. Schemes without a source keep the default.
+    fn source(_xs: &Vector<f64>, _ys: &Vector<f64>, _t: f64, _u: &Self::U) -> Option<Self::U> {
+        None
+    }
 }
@@ -276,6 +276,31 @@ pub fn assemble_grid<'grid, GS, F, FExterior, FSP>(
     Grid { elements }
 }
 
+// The time step allowed by the CFL condition for a wave travelling at `wave_speed`.
+// Each element's size is taken to be the radius of its inscribed circle, 2 A / P. Since
+// f_scale = sJ / J is the ratio of edge length to area, this is 2 / (sum of f_scale over
+// the faces). The size is then scaled by the smallest node spacing on a reference edge.
+pub fn cfl_time_step<'grid, GS: GalerkinScheme>(
+    grid: &Grid<'grid, GS>,
+    reference_element: &ReferenceElement,
+    wave_speed: f64,
+    cfl: f64,
+) -> f64 {
+    assert!(wave_speed > 0.);
+    let dr_min = reference_element.min_edge_node_spacing();
+    let h_min = grid
+        .elements
+        .iter()
+        .map(|elt| {
+            // f_scale is constant along each face of a straight-sided triangle.
+            let perimeter_over_area =
+                elt.face1.f_scale[0] + elt.face2.f_scale[0] + elt.face3.f_scale[0];
+            2. / perimeter_over_area
+        })
+        .fold(f64::INFINITY, f64::min);
+    cfl * h_min * dr_min / wave_speed
+}
+
 fn build_face<'grid, GS>(
     face_number: FaceNumber,
     face_type: FaceType<'grid, GS>,
@@ -389,6 +414,14 @@ pub trait SpatialVariable: Debug {
         }
     }
 
+    // The values on face `number` in the node order of the element across it.
+    fn neighbor_face(&self, number: FaceNumber, reference_element: &ReferenceElement) -> Self::Line {
+        Self::reverse_face(self.face(number, reference_element))
+    }
+
+    // Reverse the order of the nodes along a face.
+    fn reverse_face(face: Self::Line) -> Self::Line;
+
     fn face1_zero(reference_element: &ReferenceElement) -> Self::Line;
 
     fn face2_zero(reference_element: &ReferenceElement) -> Self::Line;
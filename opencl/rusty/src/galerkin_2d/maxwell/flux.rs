@@ -36,6 +36,10 @@ impl SpatialVariable for () {
         ()
     }
 
+    fn reverse_face(face: ()) -> () {
+        ()
+    }
+
     fn face1_zero(reference_element: &ReferenceElement) -> () {
         ()
     }
@@ -1,24 +1,34 @@
 extern crate rulinalg;
 
 use std::f64::consts;
+use checkpoint::file::{Checkpoint, CheckpointSchedule};
 use distmesh::distmesh_2d::unit_square;
+use distmesh::mesh::Mesh;
 use galerkin_2d::flux::compute_flux;
+use galerkin_2d::checkpoint::{capture, restore};
 use galerkin_2d::galerkin::GalerkinScheme;
 use galerkin_2d::grid::Element;
-use galerkin_2d::grid::{assemble_grid, Grid, SpatialVariable};
+use galerkin_2d::grid::{assemble_grid, cfl_time_step, Grid, SpatialVariable};
 use galerkin_2d::maxwell::flux::*;
 use galerkin_2d::maxwell::unknowns::*;
+use galerkin_2d::norms::error_norms;
+use galerkin_2d::convergence::convergence_study;
+use convergence::table::ConvergenceTable;
+use std::path::Path;
 use galerkin_2d::operators::curl_2d;
 use galerkin_2d::operators::grad;
 use galerkin_2d::operators::{assemble_operators, Operators};
 use galerkin_2d::reference_element::ReferenceElement;
 use galerkin_2d::unknowns::{communicate, initialize_storage, Unknown};
+use galerkin_2d::vtk::VtkSeries;
 use rulinalg::vector::Vector;
 use std::iter::repeat_with;
 use galerkin_2d::grid::ElementStorage;
 use galerkin_2d::operators::FaceLiftable;
+use functions::filter::Filter;
 use functions::range_kutta::RKA;
 use functions::range_kutta::RKB;
+use functions::range_kutta::RKC;
 use plot::plot3d::{Plotter3D, GnuplotPlotter3D};
 
 #[derive(Debug)]
@@ -31,25 +41,76 @@ impl GalerkinScheme for Maxwell2D {
     type FS = Vacuum;
 }
 
-type EHElement<'grid> = Element<'grid, Maxwell2D>;
-
 pub fn maxwell_2d<'grid, Fx>(
     grid: &Grid<Maxwell2D>,
     reference_element: &ReferenceElement,
     operators: &Operators,
     u_0: Fx,
-) where
+    final_time: f64,
+    filter: Option<&Filter>,
+    restart: Option<&Checkpoint>,
+    schedule: Option<CheckpointSchedule>,
+) -> Vec<ElementStorage<Maxwell2D>>
+where
     Fx: Fn(&Vector<f64>, &Vector<f64>) -> EH,
 {
     let mut plotter = GnuplotPlotter3D::create(-1., 1., -1., 1., -1., 1.);
 
-    let final_time = 10.0;
-    let dt: f64 = 0.003668181816046;
-    let n_t = (final_time / dt).ceil() as i32;
+    integrate_maxwell_2d(
+        grid,
+        reference_element,
+        operators,
+        u_0,
+        final_time,
+        filter,
+        restart,
+        schedule,
+        |epoch, _, storage| {
+            println!("epoch: {}", epoch);
+            if epoch % 20 == 0 {
+                plotter.header();
+                for elt in (*grid).elements.iter() {
+                    let storage = &storage[elt.index as usize];
+                    plotter.plot(&elt.x_k, &elt.y_k, &storage.u_k.Ez);
+//                    println!("{}", &storage.u_k.Hx);
+                }
+                plotter.replot();
+            }
+        },
+    )
+}
 
-    let mut t: f64 = 0.0;
+// Advance the solution from t = 0 to `final_time` with the low-storage RK4 scheme, calling
+// `after_step` at the end of every time step with the step number and the time reached.
+// Generic over the scheme so that its source term, e.g. a current density, is included.
+//
+// Given `restart`, a checkpoint of an earlier run to the same `final_time`, the run picks up
+// from there instead of from `u_0`, and the remaining steps are the same, bit for bit, as
+// those of the uninterrupted run. With a `schedule`, checkpoints are written as it goes.
+fn integrate_maxwell_2d<'grid, GS, Fx, Cb>(
+    grid: &Grid<GS>,
+    reference_element: &ReferenceElement,
+    operators: &Operators,
+    u_0: Fx,
+    final_time: f64,
+    filter: Option<&Filter>,
+    restart: Option<&Checkpoint>,
+    schedule: Option<CheckpointSchedule>,
+    mut after_step: Cb,
+) -> Vec<ElementStorage<GS>>
+where
+    GS: GalerkinScheme<U = EH, FS = Vacuum>,
+    Fx: Fn(&Vector<f64>, &Vector<f64>) -> EH,
+    Cb: FnMut(i32, f64, &Vec<ElementStorage<GS>>),
+{
+    // The usual 2 / 3 is unstable for N = 1 once the mesh is refined.
+    let cfl = 0.5;
+    let dt: f64 = cfl_time_step(grid, reference_element, 1.0, cfl);
+    let n_t = (final_time / dt).ceil() as i32;
+    // Shrink the step slightly so that the last one lands on final_time.
+    let dt = final_time / n_t as f64;
 
-    let mut storage: Vec<ElementStorage<Maxwell2D>> = initialize_storage(
+    let mut storage: Vec<ElementStorage<GS>> = initialize_storage(
         u_0,
         reference_element.n_p as i32,
         reference_element,
@@ -61,8 +122,17 @@ pub fn maxwell_2d<'grid, Fx>(
         .take(grid.elements.len())
         .collect();
 
-    for epoch in 0..n_t {
+    let (start_step, mut t) = match restart {
+        Some(checkpoint) => {
+            restore(checkpoint, reference_element.n, dt, &mut storage, &mut residuals)
+                .expect("could not resume from the checkpoint")
+        }
+        None => (0, 0.0),
+    };
+
+    for epoch in start_step..n_t {
         for int_rk in 0..5 {
+            let t = t + RKC[int_rk] * dt;
             communicate(t, reference_element, grid, &mut storage);
 
             for elt in (*grid).elements.iter() {
@@ -71,6 +141,10 @@ pub fn maxwell_2d<'grid, Fx>(
                 let residuals_eh = {
                     let residuals_eh = &(residuals[elt.index as usize]);
                     let rhs = maxwell_rhs_2d(&elt, &storage, &operators, reference_element);
+                    let rhs = match GS::source(&elt.x_k, &elt.y_k, t, &storage.u_k) {
+                        Some(source) => rhs + source,
+                        None => rhs,
+                    };
 //                    println!("{:?}", rhs);
                     residuals_eh * RKA[int_rk] + rhs * dt
                 };
@@ -84,26 +158,41 @@ pub fn maxwell_2d<'grid, Fx>(
                 storage.u_k = eh;
             }
         }
-        println!("epoch: {}", epoch);
+        if let Some(filter) = filter {
+            if filter.is_due(epoch + 1) {
+                for storage in storage.iter_mut() {
+                    let eh = &storage.u_k;
+                    storage.u_k = EH {
+                        Hx: filter.matrix() * &eh.Hx,
+                        Hy: filter.matrix() * &eh.Hy,
+                        Ez: filter.matrix() * &eh.Ez,
+                    };
+                }
+            }
+        }
         t = t + dt;
-        if epoch % 20 == 0 {
-            plotter.header();
-            for elt in (*grid).elements.iter() {
-                let storage = &storage[elt.index as usize];
-                plotter.plot(&elt.x_k, &elt.y_k, &storage.u_k.Ez);
-//                println!("{}", &storage.u_k.Hx);
+        if let Some(schedule) = schedule {
+            if schedule.is_due(epoch + 1) {
+                capture(reference_element.n, epoch + 1, t, dt, &storage, &residuals)
+                    .save(schedule.path())
+                    .expect("could not write the checkpoint");
             }
-            plotter.replot();
         }
+        after_step(epoch, t, &storage);
     }
+
+    storage
 }
 
-fn maxwell_rhs_2d<'grid>(
-    elt: &EHElement<'grid>,
-    elt_storage: &ElementStorage<Maxwell2D>,
+fn maxwell_rhs_2d<'grid, GS>(
+    elt: &Element<'grid, GS>,
+    elt_storage: &ElementStorage<GS>,
     operators: &Operators,
     reference_element: &ReferenceElement,
-) -> EH {
+) -> EH
+where
+    GS: GalerkinScheme<U = EH, FS = Vacuum>,
+{
     let (face1_flux, face2_flux, face3_flux) = compute_flux(elt, elt_storage);
 
     let flux = EH::lift_faces(
@@ -144,40 +233,144 @@ fn maxwell_rhs_2d<'grid>(
     }
 }
 
+// A grid on `mesh` for the PEC cavity: perfectly conducting walls on every boundary edge,
+// and a vacuum inside. The boundary condition has to outlive the grid, so it is passed in.
+pub fn cavity_grid<'grid, GS, F>(
+    reference_element: &ReferenceElement,
+    operators: &Operators,
+    mesh: &Mesh,
+    boundary_condition: &'grid F,
+) -> Grid<'grid, GS>
+where
+    GS: GalerkinScheme<U = EH, FS = Vacuum>,
+    F: Fn(f64) -> EH + 'grid,
+{
+    assemble_grid(
+        reference_element,
+        operators,
+        mesh,
+        boundary_condition,
+        &|| (),
+        |_, _| (),
+        MaxwellFluxType::Interior,
+        MaxwellFluxType::Exterior,
+    )
+}
+
 pub fn maxwell_2d_example() {
     let n_p = 10;
     let reference_element = ReferenceElement::legendre(n_p);
     let operators = assemble_operators(&reference_element);
     let mesh = unit_square();
     let boundary_condition = |t| EH::face1_zero(&reference_element);
-    let grid: Grid<Maxwell2D> = assemble_grid(
+    let grid: Grid<Maxwell2D> =
+        cavity_grid(&reference_element, &operators, &mesh, &boundary_condition);
+
+//    println!("{}", operators.lift);
+    let final_time = 10.0;
+    let storage = maxwell_2d(
+        &grid,
         &reference_element,
         &operators,
-        &mesh,
-        &boundary_condition,
-        &|| (),
-        |_, _| (),
-        MaxwellFluxType::Interior,
-        MaxwellFluxType::Exterior,
+        &exact_cavity_solution_eh0,
+        final_time,
+        None,
+        None,
+        None,
     );
 
-//    println!("{}", operators.lift);
-    maxwell_2d(&grid, &reference_element, &operators, &exact_cavity_solution_eh0);
+    let norms = error_norms(&grid, &storage, &operators, 0, exact_cavity_ez, final_time);
+    println!("Ez error: {:?}", norms);
+}
+
+// The names of the components of EH, in the order of `Components::components`.
+const EH_NAMES: [&str; 3] = ["Ez", "Hx", "Hy"];
+
+// Run the PEC cavity problem and write the solution every 20 steps to a series of .vtu files
+// in `maxwell_2d_vtk`, to be opened in ParaView through `maxwell_2d.pvd`.
+pub fn maxwell_2d_vtk_example() {
+    let reference_element = ReferenceElement::legendre(6);
+    let operators = assemble_operators(&reference_element);
+    let mesh = unit_square();
+    let boundary_condition = |_| EH::face1_zero(&reference_element);
+    let grid: Grid<Maxwell2D> =
+        cavity_grid(&reference_element, &operators, &mesh, &boundary_condition);
+
+    let mut series = VtkSeries::create(Path::new("maxwell_2d_vtk"), "maxwell_2d")
+        .expect("could not create the output directory");
+    integrate_maxwell_2d(
+        &grid,
+        &reference_element,
+        &operators,
+        &exact_cavity_solution_eh0,
+        2.0,
+        None,
+        None,
+        None,
+        |epoch, t, storage| {
+            if epoch % 20 == 0 {
+                series
+                    .write(t, &grid, &reference_element, storage, &EH_NAMES)
+                    .expect("could not write the snapshot");
+            }
+        },
+    );
+    println!("Wrote {}", series.pvd_path().display());
+}
+
+// Run the PEC cavity problem for each order on the unit square mesh and its refinements, and
+// tabulate the errors in Ez at `final_time`.
+fn cavity_convergence(orders: &[i32], refinements: i32, final_time: f64) -> ConvergenceTable {
+    convergence_study(
+        orders,
+        unit_square(),
+        refinements,
+        |reference_element, operators, mesh| {
+            let boundary_condition = |_| EH::face1_zero(reference_element);
+            let grid: Grid<Maxwell2D> =
+                cavity_grid(reference_element, operators, mesh, &boundary_condition);
+            let storage = integrate_maxwell_2d(
+                &grid,
+                reference_element,
+                operators,
+                &exact_cavity_solution_eh0,
+                final_time,
+                None,
+                None,
+                None,
+                |_, _, _| {},
+            );
+            error_norms(&grid, &storage, operators, 0, exact_cavity_ez, final_time)
+        },
+    )
+}
+
+// Print the p-convergence table for the cavity, and write it as CSV to `output`.
+pub fn maxwell_2d_convergence_example(output: &Path) {
+    let table = cavity_convergence(&[1, 2, 3, 4], 2, 1.0);
+    println!("{}", table);
+    table
+        .save_csv(output)
+        .expect("could not write the convergence table");
 }
 
 fn exact_cavity_solution_eh0(xs: &Vector<f64>, ys: &Vector<f64>) -> EH {
+    exact_cavity_solution(xs, ys, 0.)
+}
+
+// The (1, 1) mode of the PEC cavity [-1, 1]^2, with angular frequency pi * sqrt(2).
+fn exact_cavity_solution(xs: &Vector<f64>, ys: &Vector<f64>, t: f64) -> EH {
     let pi = consts::PI;
     let omega = pi * consts::SQRT_2;
-    let t = 0.;
     let Hx: Vector<f64> = xs.iter().zip(ys.iter()).map(|(&x, &y)| {
         -pi / omega * (pi * x).sin() * (pi * y).cos() * (omega * t).sin()
     }).collect();
     let Hy: Vector<f64> = xs.iter().zip(ys.iter()).map(|(&x, &y)| {
         pi / omega * (pi * x).cos() * (pi * y).sin() * (omega * t).sin()
     }).collect();
-    let Ez: Vector<f64> = xs.iter().zip(ys.iter()).map(|(&x, &y)| {
-        (pi * x).sin() * (pi * y).sin() * (omega * t).cos()
-    }).collect();
+    let Ez: Vector<f64> = xs.iter().zip(ys.iter())
+        .map(|(&x, &y)| exact_cavity_ez(x, y, t))
+        .collect();
 
     EH {
         Hx,
@@ -186,12 +379,255 @@ fn exact_cavity_solution_eh0(xs: &Vector<f64>, ys: &Vector<f64>) -> EH {
     }
 }
 
+fn exact_cavity_ez(x: f64, y: f64, t: f64) -> f64 {
+    let pi = consts::PI;
+    let omega = pi * consts::SQRT_2;
+    (pi * x).sin() * (pi * y).sin() * (omega * t).cos()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::maxwell_2d_example;
+    extern crate rulinalg;
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use super::{
+        cavity_convergence, cavity_grid, exact_cavity_solution_eh0, integrate_maxwell_2d,
+        maxwell_2d_example, Maxwell2D, EH_NAMES,
+    };
+    use checkpoint::file::{Checkpoint, CheckpointSchedule};
+    use distmesh::distmesh_2d::unit_square;
+    use galerkin_2d::galerkin::GalerkinScheme;
+    use galerkin_2d::grid::{ElementStorage, FaceNumber, FaceType, Grid};
+    use galerkin_2d::maxwell::flux::Vacuum;
+    use galerkin_2d::maxwell::unknowns::EH;
+    use galerkin_2d::norms::error_norms;
+    use galerkin_2d::operators::assemble_operators;
+    use galerkin_2d::reference_element::ReferenceElement;
+    use galerkin_2d::unknowns::{initialize_storage, Unknown};
+    use galerkin_2d::vtk::VtkSeries;
+    use rulinalg::vector::Vector;
+    use std::f64::consts;
+    use std::fs;
+
+    // The cavity driven at angular frequency pi by the current source
+    // pi sin(pi x) sin(pi y) sin(pi t) in Ez. Starting from the same initial data as the
+    // free cavity mode, the solution is Ez = sin(pi x) sin(pi y) cos(pi t).
+    #[derive(Debug)]
+    struct ForcedMaxwell2D {}
+
+    impl GalerkinScheme for ForcedMaxwell2D {
+        type U = EH;
+        type FS = Vacuum;
+
+        fn source(xs: &Vector<f64>, ys: &Vector<f64>, t: f64, _: &EH) -> Option<EH> {
+            let pi = consts::PI;
+            Some(EH {
+                Hx: Vector::zeros(xs.size()),
+                Hy: Vector::zeros(xs.size()),
+                Ez: xs
+                    .iter()
+                    .zip(ys.iter())
+                    .map(|(&x, &y)| pi * (pi * x).sin() * (pi * y).sin() * (pi * t).sin())
+                    .collect(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_forced_cavity() {
+        let n = 5;
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        let mesh = unit_square();
+        let boundary_condition = |_| EH::face1_zero(&reference_element);
+        let grid: Grid<ForcedMaxwell2D> =
+            cavity_grid(&reference_element, &operators, &mesh, &boundary_condition);
+
+        let final_time = 0.5;
+        let storage = integrate_maxwell_2d(
+            &grid,
+            &reference_element,
+            &operators,
+            &exact_cavity_solution_eh0,
+            final_time,
+            None,
+            None,
+            None,
+            |_, _, _| {},
+        );
+
+        let pi = consts::PI;
+        let exact_ez = |x: f64, y: f64, t: f64| (pi * x).sin() * (pi * y).sin() * (pi * t).cos();
+        let ez = error_norms(&grid, &storage, &operators, 0, exact_ez, final_time);
+        assert!(ez.l_inf < 1e-4);
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let n = 3;
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        let mesh = unit_square();
+        let boundary_condition = |_| EH::face1_zero(&reference_element);
+        let grid: Grid<Maxwell2D> =
+            cavity_grid(&reference_element, &operators, &mesh, &boundary_condition);
+        let final_time = 0.3;
+
+        let dir = tempdir().expect("could not create a temporary directory");
+        let path = dir.path().join("maxwell_2d.chk");
+        let schedule = CheckpointSchedule::new(&path, 5);
+        let uninterrupted = integrate_maxwell_2d(
+            &grid,
+            &reference_element,
+            &operators,
+            &exact_cavity_solution_eh0,
+            final_time,
+            None,
+            None,
+            Some(schedule),
+            |_, _, _| {},
+        );
+
+        let checkpoint = Checkpoint::load(&path).expect("could not read the checkpoint");
+        assert!(checkpoint.step > 0 && checkpoint.time < final_time);
+        let resumed = integrate_maxwell_2d(
+            &grid,
+            &reference_element,
+            &operators,
+            &exact_cavity_solution_eh0,
+            final_time,
+            None,
+            Some(&checkpoint),
+            None,
+            |_, _, _| {},
+        );
+
+        let bits = |storage: &Vec<ElementStorage<Maxwell2D>>| -> Vec<u64> {
+            storage
+                .iter()
+                .flat_map(|s| s.u_k.Ez.iter().chain(s.u_k.Hx.iter()).chain(s.u_k.Hy.iter()))
+                .map(|x| x.to_bits())
+                .collect()
+        };
+        assert_eq!(bits(&resumed), bits(&uninterrupted));
+    }
+
+    #[test]
+    fn test_vtk_series() {
+        let n = 3;
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        let mesh = unit_square();
+        let boundary_condition = |_| EH::face1_zero(&reference_element);
+        let grid: Grid<Maxwell2D> =
+            cavity_grid(&reference_element, &operators, &mesh, &boundary_condition);
+
+        let dir = tempdir().expect("could not create a temporary directory");
+        let mut series = VtkSeries::create(dir.path(), "cavity").expect("could not create it");
+        let mut paths = vec![];
+        integrate_maxwell_2d(
+            &grid,
+            &reference_element,
+            &operators,
+            &exact_cavity_solution_eh0,
+            0.05,
+            None,
+            None,
+            None,
+            |_, t, storage| {
+                let path = series
+                    .write(t, &grid, &reference_element, storage, &EH_NAMES)
+                    .expect("could not write the snapshot");
+                paths.push(path);
+            },
+        );
+        assert!(paths.len() > 1);
+
+        // Each element keeps its own 10 nodes and is split into 9 sub-triangles.
+        let n_elements = grid.elements.len();
+        let vtu = fs::read_to_string(&paths[0]).expect("could not read the snapshot");
+        assert!(vtu.contains(&format!(
+            "<Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">",
+            10 * n_elements,
+            9 * n_elements
+        )));
+        for name in EH_NAMES.iter() {
+            assert!(vtu.contains(&format!("Name=\"{}\"", name)));
+        }
+        let offsets = vtu
+            .lines()
+            .skip_while(|line| !line.contains("Name=\"offsets\""))
+            .nth(1)
+            .expect("no offsets");
+        assert_eq!(offsets.split_whitespace().count(), 9 * n_elements);
+
+        let pvd = fs::read_to_string(series.pvd_path()).expect("could not read the collection");
+        assert_eq!(pvd.matches("<DataSet").count(), paths.len());
+    }
 
     #[test]
     pub fn test_maxwell_2d() {
         maxwell_2d_example();
     }
+
+    #[test]
+    fn test_neighbor_faces_line_up() {
+        let n = 3;
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        let mesh = unit_square();
+        let boundary_condition = |_| EH::face1_zero(&reference_element);
+        let grid: Grid<Maxwell2D> =
+            cavity_grid(&reference_element, &operators, &mesh, &boundary_condition);
+        let u_0 = |xs: &Vector<f64>, ys: &Vector<f64>| EH {
+            Ez: xs.clone(),
+            Hx: ys.clone(),
+            Hy: Vector::zeros(xs.size()),
+        };
+        let storage = initialize_storage(
+            u_0,
+            reference_element.n_p as i32,
+            &reference_element,
+            &grid,
+            &operators,
+        );
+
+        // Each node on a shared edge must meet the neighbor's node at the same point.
+        for elt in grid.elements.iter() {
+            let faces = [
+                (FaceNumber::One, &elt.face1),
+                (FaceNumber::Two, &elt.face2),
+                (FaceNumber::Three, &elt.face3),
+            ];
+            for &(number, face) in faces.iter() {
+                if let FaceType::Interior(j, neighbor_number) = face.face_type {
+                    let own = storage[elt.index as usize]
+                        .u_k
+                        .face(number, &reference_element);
+                    let neighbor = storage[j as usize]
+                        .u_k
+                        .neighbor_face(neighbor_number, &reference_element);
+                    let close = |a: &Vector<f64>, b: &Vector<f64>| {
+                        a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 1e-12)
+                    };
+                    assert!(close(&own.Ez, &neighbor.Ez));
+                    assert!(close(&own.Hx, &neighbor.Hx));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cavity_convergence() {
+        let orders = [1, 2, 3];
+        let table = cavity_convergence(&orders, 1, 0.5);
+
+        // The upwind scheme converges at the optimal order N + 1 in L2.
+        for &n in orders.iter() {
+            let finest = table.order(n)[1];
+            assert!(finest.l2_rate.expect("not the coarsest run") > n as f64 + 0.7);
+            assert!(finest.h1_seminorm_rate.expect("not the coarsest run") > n as f64 - 0.7);
+        }
+    }
 }
@@ -1,3 +1,3 @@
 mod flux;
 pub mod maxwell;
-mod unknowns;
+pub mod unknowns;
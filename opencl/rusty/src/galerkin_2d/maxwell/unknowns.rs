@@ -7,7 +7,7 @@ use galerkin_2d::operators::FaceLift;
 use galerkin_2d::operators::FaceLiftable;
 use galerkin_2d::operators::{assemble_operators, Operators};
 use galerkin_2d::reference_element::ReferenceElement;
-use galerkin_2d::unknowns::Unknown;
+use galerkin_2d::unknowns::{Components, Unknown};
 use rulinalg::vector::Vector;
 use std::ops::{Add, Div, Mul, Neg, Sub};
 use std::fmt;
@@ -89,6 +89,14 @@ impl Unknown for EH {
         }
     }
 
+    fn reverse_face(face: EH) -> EH {
+        EH {
+            Ez: face.Ez.into_iter().rev().collect(),
+            Hx: face.Hx.into_iter().rev().collect(),
+            Hy: face.Hy.into_iter().rev().collect(),
+        }
+    }
+
     fn zero(reference_element: &ReferenceElement) -> Self {
         EH {
             Ez: Vector::zeros(reference_element.n_p),
@@ -122,6 +130,19 @@ impl Unknown for EH {
     }
 }
 
+impl Components for EH {
+    fn components(&self) -> Vec<&Vector<f64>> {
+        vec![&self.Ez, &self.Hx, &self.Hy]
+    }
+
+    fn from_components(mut components: Vec<Vector<f64>>) -> EH {
+        let Hy = components.remove(2);
+        let Hx = components.remove(1);
+        let Ez = components.remove(0);
+        EH { Ez, Hx, Hy }
+    }
+}
+
 impl Neg for EH {
     type Output = Self;
 
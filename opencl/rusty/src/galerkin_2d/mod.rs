@@ -1,8 +1,12 @@
+mod checkpoint;
+mod convergence;
 mod flux;
 mod galerkin;
 mod grid;
+mod norms;
 mod operators;
 mod reference_element;
 mod unknowns;
+mod vtk;
 
 pub mod maxwell;
@@ -0,0 +1,119 @@
+extern crate rulinalg;
+
+use galerkin_2d::galerkin::GalerkinScheme;
+use galerkin_2d::grid::{ElementStorage, Grid};
+use galerkin_2d::operators::{grad, Operators};
+use galerkin_2d::unknowns::Components;
+use rulinalg::vector::Vector;
+
+// Norms of the difference between the discrete solution and an exact solution, taken over
+// the whole grid.
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorNorms {
+    pub l2: f64,
+    pub l_inf: f64,
+    // The L2 norm of the gradient of the error.
+    pub h1_seminorm: f64,
+}
+
+// The errors in one component of the solution against `exact`, a function of x, y and t.
+// The error is sampled at the nodes and integrated with the element mass matrix (V V^T)^-1
+// scaled by the Jacobian, so these are the norms of its degree-N interpolant.
+pub fn error_norms<GS, Fx>(
+    grid: &Grid<GS>,
+    storages: &Vec<ElementStorage<GS>>,
+    operators: &Operators,
+    component: usize,
+    exact: Fx,
+    t: f64,
+) -> ErrorNorms
+where
+    GS: GalerkinScheme,
+    GS::U: Components,
+    Fx: Fn(f64, f64, f64) -> f64,
+{
+    let mass = &operators.mass;
+
+    let mut l2_squared = 0.;
+    let mut h1_squared = 0.;
+    let mut l_inf: f64 = 0.;
+    for elt in grid.elements.iter() {
+        let storage = &storages[elt.index as usize];
+        let u = storage.u_k.components()[component];
+        let error: Vector<f64> = elt
+            .x_k
+            .iter()
+            .zip(elt.y_k.iter())
+            .zip(u.iter())
+            .map(|((&x, &y), &u)| u - exact(x, y, t))
+            .collect();
+        let grad_error = grad(&error, operators, &elt.local_metric);
+
+        // The elements are straight-sided, so the Jacobian is constant on each.
+        let jacobian = elt.local_metric.jacobian[0];
+        l2_squared += jacobian * error.dot(&(mass * &error));
+        h1_squared += jacobian
+            * (grad_error.x.dot(&(mass * &grad_error.x))
+                + grad_error.y.dot(&(mass * &grad_error.y)));
+        l_inf = error.iter().fold(l_inf, |acc, e| {
+            if e.is_nan() || e.abs() > acc {
+                e.abs()
+            } else {
+                acc
+            }
+        });
+    }
+
+    ErrorNorms {
+        l2: l2_squared.sqrt(),
+        l_inf,
+        h1_seminorm: h1_squared.sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::error_norms;
+    use distmesh::distmesh_2d::unit_square;
+    use galerkin_2d::grid::Grid;
+    use galerkin_2d::maxwell::maxwell::{cavity_grid, Maxwell2D};
+    use galerkin_2d::maxwell::unknowns::EH;
+    use galerkin_2d::operators::assemble_operators;
+    use galerkin_2d::reference_element::ReferenceElement;
+    use galerkin_2d::unknowns::{initialize_storage, Unknown};
+    use rulinalg::vector::Vector;
+
+    #[test]
+    fn test_error_norms() {
+        let n = 4;
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        let mesh = unit_square();
+        let boundary_condition = |_| EH::face1_zero(&reference_element);
+        let grid: Grid<Maxwell2D> =
+            cavity_grid(&reference_element, &operators, &mesh, &boundary_condition);
+
+        // Ez = x + 1 and Hx = x on [-1, 1]^2, against the exact solutions Ez = x and Hx = 0.
+        let u_0 = |xs: &Vector<f64>, ys: &Vector<f64>| EH {
+            Ez: xs + 1.,
+            Hx: xs.clone(),
+            Hy: Vector::zeros(ys.size()),
+        };
+        let storage = initialize_storage(
+            u_0,
+            reference_element.n_p as i32,
+            &reference_element,
+            &grid,
+            &operators,
+        );
+
+        let ez = error_norms(&grid, &storage, &operators, 0, |x, _, _| x, 0.);
+        assert!((ez.l2 - 2.).abs() < 1e-10);
+        assert!((ez.l_inf - 1.).abs() < 1e-10);
+        assert!(ez.h1_seminorm < 1e-10);
+
+        let hx = error_norms(&grid, &storage, &operators, 1, |_, _, _| 0., 0.);
+        assert!((hx.l2 - (4f64 / 3.).sqrt()).abs() < 1e-10);
+        assert!((hx.h1_seminorm - 2.).abs() < 1e-10);
+    }
+}
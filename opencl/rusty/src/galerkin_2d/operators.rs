@@ -1,6 +1,7 @@
 extern crate itertools;
 extern crate rulinalg;
 
+use functions::filter::exponential_filter;
 use functions::vandermonde::{grad_vandermonde_2d, vandermonde, vandermonde_2d};
 use galerkin_2d::grid::Element;
 use galerkin_2d::grid::XYTuple;
@@ -16,16 +17,40 @@ pub struct Operators {
     // The Vandermonde matrix
     pub v: Matrix<f64>,
 
+    // The inverse Vandermonde matrix, mapping nodal values to modal coefficients
+    pub v_inv: Matrix<f64>,
+
+    // The mass matrix M = (V V^T)^-1
+    pub mass: Matrix<f64>,
+
+    // The inverse mass matrix V V^T
+    pub inv_mass: Matrix<f64>,
+
     // The D_r derivative matrix. D_r*V = V_r
     pub d_r: Matrix<f64>,
     // The D_s derivative matrix. D_s*V = V_s
     pub d_s: Matrix<f64>,
 
+    // The stiffness matrices S_r = M D_r and S_s = M D_s
+    pub s_r: Matrix<f64>,
+    pub s_s: Matrix<f64>,
+
+    // The one-dimensional mass matrices of the face nodes, in the order they are listed by
+    // the reference element.
+    pub face_mass: FaceMass,
+
     // The matrix lifting the surface integral on the simplex edges to the
     // area integral over the simplex.
     pub lift: FaceLift,
 }
 
+#[derive(Debug)]
+pub struct FaceMass {
+    pub face1: Matrix<f64>,
+    pub face2: Matrix<f64>,
+    pub face3: Matrix<f64>,
+}
+
 #[derive(Debug)]
 pub struct FaceLift {
     pub face1: Matrix<f64>,
@@ -69,78 +94,89 @@ pub fn assemble_operators(reference_element: &ReferenceElement) -> Operators {
     let d_r = &v_r * &v_inv;
     let d_s = &v_s * &v_inv;
 
-    let lift = assemble_lift(reference_element, &v);
+    let inv_mass = &v * v.transpose();
+    let mass = inv_mass
+        .clone()
+        .inverse()
+        .expect("Non-invertible inverse mass matrix");
+    let s_r = &mass * &d_r;
+    let s_s = &mass * &d_s;
+
+    let face_mass = assemble_face_mass(reference_element);
+    let lift = assemble_lift(reference_element, &inv_mass, &face_mass);
 
-    Operators { v, d_r, d_s, lift }
+    Operators {
+        v,
+        v_inv,
+        mass,
+        inv_mass,
+        d_r,
+        d_s,
+        s_r,
+        s_s,
+        face_mass,
+        lift,
+    }
 }
 
-fn assemble_lift(reference_element: &ReferenceElement, v2d: &Matrix<f64>) -> FaceLift {
-    let inv_mass_matrix = v2d * v2d.transpose();
-    let n = reference_element.n as i32;
-    let n_p = (n + 1) * (n + 2) / 2;
-    let n_fp: usize = (n + 1) as usize;
-    let epsilon = 1.0e-12;
+impl Operators {
+    // The exponential filter V * diag(sigma) * V^-1, which leaves modes of total degree up
+    // to `cutoff` alone and damps the higher ones. `order` must be even.
+    pub fn filter(&self, cutoff: i32, order: i32) -> Matrix<f64> {
+        let n_p = self.v.cols() as i32;
+        let mut n = 0;
+        while (n + 1) * (n + 2) / 2 < n_p {
+            n = n + 1;
+        }
 
-    let ss = &reference_element.ss;
+        // The modes are ordered as in vandermonde_2d.
+        let mut mode_orders: Vec<i32> = vec![];
+        (0..n + 1).for_each(|i| (0..n + 1 - i).for_each(|j| mode_orders.push(i + j)));
+        exponential_filter(&self.v, &self.v_inv, &mode_orders, n, cutoff, order)
+    }
+}
+
+// The mass matrix of the nodes on each face, using r as the coordinate along faces 1 and 2
+// and s along face 3.
+fn assemble_face_mass(reference_element: &ReferenceElement) -> FaceMass {
+    let n = reference_element.n as i32;
     let rs = &reference_element.rs;
+    let ss = &reference_element.ss;
 
-    let mut face1: Matrix<f64> = Matrix::zeros(n_p as usize, n_fp as usize);
-    let face1_r: Vector<f64> = rs.select(&reference_element.face1.as_slice());
-    let v = vandermonde(&face1_r, n);
-    let mass_face1 = (&v * &v.transpose()).inverse().expect("non-invertible");
-    &reference_element
-        .face1
-        .iter()
-        .enumerate()
-        .for_each(|(j, &i)| {
-            face1
-                .row_mut(i as usize)
-                .iter_mut()
-                .zip(mass_face1.row(j).into_iter())
-                .for_each(|(dest, x)| *dest = *x)
-        });
-    let lift_face_1 = &inv_mass_matrix * face1;
-
-    let mut face2: Matrix<f64> = Matrix::zeros(n_p as usize, n_fp as usize);
-    // Can use either r or s here; the important thing is that they are distributed in the
-    // same way along the diagonal edge.
-    let face2_r: Vector<f64> = rs.select(&reference_element.face2.as_slice());
-    let v = vandermonde(&face2_r, n);
-    let mass_face2 = (&v * &v.transpose()).inverse().expect("non-invertible");
-    &reference_element
-        .face2
-        .iter()
-        .enumerate()
-        .for_each(|(j, &i)| {
-            face2
-                .row_mut(i as usize)
-                .iter_mut()
-                .zip(mass_face1.row(j).into_iter())
-                .for_each(|(dest, x)| *dest = *x)
-        });
-    let lift_face_2 = &inv_mass_matrix * face2;
-
-    let mut face3: Matrix<f64> = Matrix::zeros(n_p as usize, n_fp as usize);
-    let face3_s: Vector<f64> = ss.select(&reference_element.face3.as_slice());
-    let v = vandermonde(&face3_s, n_p);
-    let mass_face3 = (&v * &v.transpose()).inverse().expect("non-invertible");
-    &reference_element
-        .face3
-        .iter()
-        .enumerate()
-        .for_each(|(j, &i)| {
-            face3
-                .row_mut(i as usize)
+    let edge_mass = |coordinates: &Vector<f64>, face: &Vec<usize>| {
+        let v = vandermonde(&coordinates.select(face.as_slice()), n);
+        (&v * &v.transpose()).inverse().expect("non-invertible")
+    };
+
+    FaceMass {
+        face1: edge_mass(rs, &reference_element.face1),
+        face2: edge_mass(rs, &reference_element.face2),
+        face3: edge_mass(ss, &reference_element.face3),
+    }
+}
+
+// LIFT = M^-1 E for each face, where E places the face mass matrix in the rows of the face
+// nodes.
+fn assemble_lift(
+    reference_element: &ReferenceElement,
+    inv_mass: &Matrix<f64>,
+    face_mass: &FaceMass,
+) -> FaceLift {
+    let lift_face = |face: &Vec<usize>, mass_face: &Matrix<f64>| {
+        let mut e: Matrix<f64> = Matrix::zeros(reference_element.n_p, face.len());
+        face.iter().enumerate().for_each(|(j, &i)| {
+            e.row_mut(i)
                 .iter_mut()
-                .zip(mass_face1.row(j).into_iter())
+                .zip(mass_face.row(j).into_iter())
                 .for_each(|(dest, x)| *dest = *x)
         });
-    let lift_face_3 = &inv_mass_matrix * face3;
+        inv_mass * e
+    };
 
     FaceLift {
-        face1: lift_face_1,
-        face2: lift_face_2,
-        face3: lift_face_3,
+        face1: lift_face(&reference_element.face1, &face_mass.face1),
+        face2: lift_face(&reference_element.face2, &face_mass.face2),
+        face3: lift_face(&reference_element.face3, &face_mass.face3),
     }
 }
 
@@ -170,3 +206,102 @@ pub fn curl_2d(
         - u_xr.elemul(&local_metric.r_y) - u_xs.elemul(&local_metric.s_y);
     v_z
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate rulinalg;
+
+    use super::assemble_operators;
+    use galerkin_2d::reference_element::ReferenceElement;
+    use rulinalg::matrix::{BaseMatrix, Matrix};
+    use rulinalg::norm::Euclidean;
+    use rulinalg::vector::Vector;
+
+    #[test]
+    fn test_mass_and_stiffness() {
+        let n = 4;
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        let ones = Vector::ones(reference_element.n_p);
+        let rs = &reference_element.rs;
+        let ss = &reference_element.ss;
+
+        // The reference triangle has area 2, and each face is parametrized over [-1, 1].
+        assert!((ones.dot(&(&operators.mass * &ones)) - 2.).abs() < 1e-10);
+        let face_mass = &operators.face_mass;
+        for mass_face in vec![&face_mass.face1, &face_mass.face2, &face_mass.face3] {
+            let ones = Vector::ones(mass_face.rows());
+            assert!((ones.dot(&(mass_face * &ones)) - 2.).abs() < 1e-10);
+        }
+
+        // The integrals of dr/dr and ds/ds over the triangle are its area, and those of
+        // ds/dr and dr/ds vanish.
+        assert!((ones.dot(&(&operators.s_r * rs)) - 2.).abs() < 1e-10);
+        assert!((ones.dot(&(&operators.s_s * ss)) - 2.).abs() < 1e-10);
+        assert!(ones.dot(&(&operators.s_r * ss)).abs() < 1e-10);
+        assert!(ones.dot(&(&operators.s_s * rs)).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_lift_matches_face_one_mass() {
+        // The lift used to place face 1's mass matrix on every face. The nodes of each face
+        // are spread alike along [-1, 1], so building each face from its own mass matrix
+        // must give the same operators.
+        for n in 1..6 {
+            let reference_element = ReferenceElement::legendre(n);
+            let operators = assemble_operators(&reference_element);
+            let mass_face1 = &operators.face_mass.face1;
+            let lift = &operators.lift;
+            let faces = [
+                (&reference_element.face1, &lift.face1),
+                (&reference_element.face2, &lift.face2),
+                (&reference_element.face3, &lift.face3),
+            ];
+            for &(face, lift_face) in faces.iter() {
+                let mut e: Matrix<f64> = Matrix::zeros(reference_element.n_p, face.len());
+                for (j, &i) in face.iter().enumerate() {
+                    for k in 0..face.len() {
+                        e[[i, k]] = mass_face1[[j, k]];
+                    }
+                }
+                let old = &operators.inv_mass * e;
+                let difference = lift_face - &old;
+                assert!(difference.iter().all(|x| x.abs() < 1e-10));
+            }
+        }
+    }
+
+    #[test]
+    fn test_lift_integrates_face_values() {
+        // The lifted face term integrates over the triangle to the integral along the face.
+        let n = 3;
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        let ones = Vector::ones(reference_element.n_p);
+        let face_ones = Vector::ones(reference_element.face1.len());
+        let lift = &operators.lift;
+        for lift_face in vec![&lift.face1, &lift.face2, &lift.face3] {
+            let lifted = lift_face * &face_ones;
+            assert!((ones.dot(&(&operators.mass * lifted)) - 2.).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_filter_damps_high_modes() {
+        let n = 4;
+        let reference_element = ReferenceElement::legendre(n);
+        let operators = assemble_operators(&reference_element);
+        let filter = operators.filter(2, 4);
+        let n_p = operators.v.cols();
+
+        // The first modes have total degrees 0, 1 and 2, and pass through unchanged.
+        for i in 0..3 {
+            let mode = operators.v.col(i).iter().cloned().collect::<Vector<f64>>();
+            assert!((&filter * &mode - &mode).norm(Euclidean) < 1e-10);
+        }
+
+        // The last mode has total degree n, and is damped to machine precision.
+        let mode = operators.v.col(n_p - 1).iter().cloned().collect::<Vector<f64>>();
+        assert!((&filter * &mode).norm(Euclidean) < 1e-10);
+    }
+}
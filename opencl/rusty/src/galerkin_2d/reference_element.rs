@@ -29,6 +29,8 @@ pub struct ReferenceElement {
     pub rs: Vector<f64>,
     pub ss: Vector<f64>,
 
+    // The nodes on each face, listed counter-clockwise around the triangle. Two elements
+    // sharing an edge therefore see its nodes in opposite orders.
     pub face1: Vec<usize>,
     pub face2: Vec<usize>,
     pub face3: Vec<usize>,
@@ -43,6 +45,35 @@ impl ReferenceElement {
         }
     }
 
+    // The smallest distance between two adjacent nodes along an edge of the reference
+    // triangle, measured on the r = [-1, 1] edge.
+    pub fn min_edge_node_spacing(&self) -> f64 {
+        let mut rs: Vec<f64> = self.face1.iter().map(|&i| self.rs[i]).collect();
+        rs.sort_by(|a, b| a.partial_cmp(b).expect("NaN interpolation point"));
+        rs.windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    // A triangulation of the reference triangle with the nodes as its vertices, for plotting.
+    // The nodes are numbered row by row from s = -1, with r increasing along each row, so
+    // node j of row i is at i (2N + 3 - i) / 2 + j. Every pair of adjacent rows
+    // is split into 2(N - i) - 1 triangles, listed counter-clockwise, which makes N^2 in all.
+    pub fn sub_triangles(&self) -> Vec<[usize; 3]> {
+        let n = self.n as usize;
+        let node = |i: usize, j: usize| i * (2 * n + 3 - i) / 2 + j;
+        let mut triangles = Vec::with_capacity(n * n);
+        for i in 0..n {
+            for j in 0..n - i {
+                triangles.push([node(i, j), node(i, j + 1), node(i + 1, j)]);
+                if j + 1 < n - i {
+                    triangles.push([node(i, j + 1), node(i + 1, j + 1), node(i + 1, j)]);
+                }
+            }
+        }
+        triangles
+    }
+
     pub fn legendre(n: i32) -> ReferenceElement {
         let n_p = (n + 1) * (n + 2) / 2;
         let (x, y) = ReferenceElement::equilateral_nodes(n);
@@ -64,6 +95,7 @@ impl ReferenceElement {
         let face3: Vec<usize> = (0..rs.size())
             .into_iter()
             .filter(|i| (rs[*i] + 1.).abs() < EPSILON)
+            .rev()
             .collect();
 
         let points: Vec<ReferencePoint> = rs
@@ -198,6 +230,23 @@ mod tests {
         assert_eq!(actual[1], -0.24330999741877501);
     }
 
+    #[test]
+    fn test_sub_triangles() {
+        let re = ReferenceElement::legendre(4);
+        let triangles = re.sub_triangles();
+        assert_eq!(triangles.len(), 16);
+
+        // The triangles are counter-clockwise and tile the reference triangle, of area 2.
+        let area = |t: &[usize; 3]| {
+            let (r, s) = (&re.rs, &re.ss);
+            ((r[t[1]] - r[t[0]]) * (s[t[2]] - s[t[0]]) - (r[t[2]] - r[t[0]]) * (s[t[1]] - s[t[0]]))
+                / 2.
+        };
+        assert!(triangles.iter().all(|t| area(t) > 0.));
+        let total: f64 = triangles.iter().map(area).sum();
+        assert!((total - 2.).abs() < 1e-12);
+    }
+
     #[test]
     fn test_reference_element() {
         let re = ReferenceElement::legendre(10);
@@ -46,6 +46,16 @@ pub trait Unknown
         }
     }
 
+    // The values on face `number` in the node order of the element across it. Faces list
+    // their nodes counter-clockwise, so for a mesh of counter-clockwise triangles the two
+    // sides of an edge traverse it in opposite directions.
+    fn neighbor_face(&self, number: FaceNumber, reference_element: &ReferenceElement) -> Self::Line {
+        Self::reverse_face(self.face(number, reference_element))
+    }
+
+    // Reverse the order of the nodes along a face.
+    fn reverse_face(face: Self::Line) -> Self::Line;
+
     fn face1_zero(reference_element: &ReferenceElement) -> Self::Line;
 
     fn face2_zero(reference_element: &ReferenceElement) -> Self::Line;
@@ -53,6 +63,14 @@ pub trait Unknown
     fn face3_zero(reference_element: &ReferenceElement) -> Self::Line;
 }
 
+// An unknown made up of scalar fields that can be operated on one at a time, for example
+// when measuring errors.
+pub trait Components: Unknown {
+    fn components(&self) -> Vec<&Vector<f64>>;
+
+    fn from_components(components: Vec<Vector<f64>>) -> Self;
+}
+
 pub fn initialize_storage<GS, Fx>(
     u_0: Fx,
     n_p: i32,
@@ -71,7 +89,7 @@ pub fn initialize_storage<GS, Fx>(
                 elt.spatial_parameters.edge_1(reference_element),
                 grid.elements[j as usize]
                     .spatial_parameters
-                    .face(face_number, reference_element),
+                    .neighbor_face(face_number, reference_element),
             ),
             FaceType::Boundary(_, f) => (
                 elt.spatial_parameters.edge_1(reference_element),
@@ -83,7 +101,7 @@ pub fn initialize_storage<GS, Fx>(
                 elt.spatial_parameters.edge_2(reference_element),
                 grid.elements[j as usize]
                     .spatial_parameters
-                    .face(face_number, reference_element),
+                    .neighbor_face(face_number, reference_element),
             ),
             FaceType::Boundary(_, f) => (
                 elt.spatial_parameters.edge_2(reference_element),
@@ -95,7 +113,7 @@ pub fn initialize_storage<GS, Fx>(
                 elt.spatial_parameters.edge_3(reference_element),
                 grid.elements[j as usize]
                     .spatial_parameters
-                    .face(face_number, reference_element),
+                    .neighbor_face(face_number, reference_element),
             ),
             FaceType::Boundary(_, f) => (
                 elt.spatial_parameters.edge_3(reference_element),
@@ -139,7 +157,7 @@ pub fn communicate<GS>(
             FaceType::Interior(j, face_number) => {
                 let u_k_neighbor: &GS::U = &storages[j as usize].u_k;
                 // minus is interior, plus is neighbor
-                (face1, u_k_neighbor.face(face_number, reference_element))
+                (face1, u_k_neighbor.neighbor_face(face_number, reference_element))
             }
             FaceType::Boundary(bc, _) => {
                 // minus is interior, plus is neighbor
@@ -154,7 +172,7 @@ pub fn communicate<GS>(
             FaceType::Interior(j, face_number) => {
                 let u_k_neighbor: &GS::U = &storages[j as usize].u_k;
                 // minus is interior, plus is neighbor
-                (face2, u_k_neighbor.face(face_number, reference_element))
+                (face2, u_k_neighbor.neighbor_face(face_number, reference_element))
             }
             FaceType::Boundary(bc, _) => {
                 // minus is interior, plus is neighbor
@@ -169,7 +187,7 @@ pub fn communicate<GS>(
             FaceType::Interior(j, face_number) => {
                 let u_k_neighbor: &GS::U = &storages[j as usize].u_k;
                 // minus is interior, plus is neighbor
-                (face3, u_k_neighbor.face(face_number, reference_element))
+                (face3, u_k_neighbor.neighbor_face(face_number, reference_element))
             }
             FaceType::Boundary(bc, _) => {
                 // minus is interior, plus is neighbor
@@ -0,0 +1,223 @@
+use galerkin_2d::galerkin::GalerkinScheme;
+use galerkin_2d::grid::{ElementStorage, Grid};
+use galerkin_2d::reference_element::ReferenceElement;
+use galerkin_2d::unknowns::Components;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// The VTK cell type of a linear triangle.
+const VTK_TRIANGLE: u8 = 5;
+
+// A time series of snapshots of a solution, written as one .vtu file per snapshot and a
+// .pvd file listing them, which ParaView opens as a single dataset over time.
+pub struct VtkSeries {
+    dir: PathBuf,
+    name: String,
+    // The time and file name of each snapshot written so far.
+    snapshots: Vec<(f64, String)>,
+}
+
+impl VtkSeries {
+    // A series of files `name_0000.vtu`, `name_0001.vtu`, ... and `name.pvd` in `dir`, which
+    // is created if it does not exist.
+    pub fn create(dir: &Path, name: &str) -> io::Result<VtkSeries> {
+        fs::create_dir_all(dir)?;
+        Ok(VtkSeries {
+            dir: dir.to_path_buf(),
+            name: name.to_string(),
+            snapshots: vec![],
+        })
+    }
+
+    pub fn pvd_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.pvd", self.name))
+    }
+
+    // Write the solution at time t, with its components named by `names`. The .pvd file is
+    // rewritten each time, so that the series is readable even if the run stops early.
+    pub fn write<GS>(
+        &mut self,
+        t: f64,
+        grid: &Grid<GS>,
+        reference_element: &ReferenceElement,
+        storages: &Vec<ElementStorage<GS>>,
+        names: &[&str],
+    ) -> io::Result<PathBuf>
+    where
+        GS: GalerkinScheme,
+        GS::U: Components,
+    {
+        let file_name = format!("{}_{:04}.vtu", self.name, self.snapshots.len());
+        let path = self.dir.join(&file_name);
+        let mut file = BufWriter::new(File::create(&path)?);
+        write_vtu(&mut file, grid, reference_element, storages, names)?;
+        file.flush()?;
+        self.snapshots.push((t, file_name));
+
+        let mut pvd = BufWriter::new(File::create(self.pvd_path())?);
+        write_pvd(&mut pvd, &self.snapshots)?;
+        pvd.flush()?;
+        Ok(path)
+    }
+}
+
+// Write the solution as a VTK unstructured grid in the ASCII XML format. Each element keeps
+// its own copy of its nodes, so the discontinuities between elements are preserved, and is
+// split into the sub-triangles of `ReferenceElement::sub_triangles`. Each sub-triangle
+// carries the index of its element as cell data.
+pub fn write_vtu<W, GS>(
+    w: &mut W,
+    grid: &Grid<GS>,
+    reference_element: &ReferenceElement,
+    storages: &Vec<ElementStorage<GS>>,
+    names: &[&str],
+) -> io::Result<()>
+where
+    W: Write,
+    GS: GalerkinScheme,
+    GS::U: Components,
+{
+    let n_p = reference_element.n_p;
+    let sub_triangles = reference_element.sub_triangles();
+    let n_points = grid.elements.len() * n_p;
+    let n_cells = grid.elements.len() * sub_triangles.len();
+
+    writeln!(w, "<?xml version=\"1.0\"?>")?;
+    writeln!(
+        w,
+        "<VTKFile type=\"UnstructuredGrid\" version=\"0.1\" byte_order=\"LittleEndian\">"
+    )?;
+    writeln!(w, "  <UnstructuredGrid>")?;
+    writeln!(
+        w,
+        "    <Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">",
+        n_points, n_cells
+    )?;
+
+    writeln!(w, "      <PointData>")?;
+    for (component, name) in names.iter().enumerate() {
+        begin_data_array(w, "Float64", name, 1)?;
+        for elt in grid.elements.iter() {
+            let fields = storages[elt.index as usize].u_k.components();
+            assert_eq!(fields.len(), names.len(), "one name is needed per component");
+            for value in fields[component].iter() {
+                write!(w, " {}", value)?;
+            }
+            writeln!(w)?;
+        }
+        end_data_array(w)?;
+    }
+    writeln!(w, "      </PointData>")?;
+
+    writeln!(w, "      <CellData>")?;
+    begin_data_array(w, "Int32", "element", 1)?;
+    for elt in grid.elements.iter() {
+        for _ in sub_triangles.iter() {
+            write!(w, " {}", elt.index)?;
+        }
+        writeln!(w)?;
+    }
+    end_data_array(w)?;
+    writeln!(w, "      </CellData>")?;
+
+    writeln!(w, "      <Points>")?;
+    begin_data_array(w, "Float64", "points", 3)?;
+    for elt in grid.elements.iter() {
+        for (x, y) in elt.x_k.iter().zip(elt.y_k.iter()) {
+            write!(w, " {} {} 0", x, y)?;
+        }
+        writeln!(w)?;
+    }
+    end_data_array(w)?;
+    writeln!(w, "      </Points>")?;
+
+    writeln!(w, "      <Cells>")?;
+    begin_data_array(w, "Int32", "connectivity", 1)?;
+    for elt in grid.elements.iter() {
+        let first = elt.index as usize * n_p;
+        for triangle in sub_triangles.iter() {
+            write!(
+                w,
+                " {} {} {}",
+                first + triangle[0],
+                first + triangle[1],
+                first + triangle[2]
+            )?;
+        }
+        writeln!(w)?;
+    }
+    end_data_array(w)?;
+    begin_data_array(w, "Int32", "offsets", 1)?;
+    for cell in 0..n_cells {
+        write!(w, " {}", 3 * (cell + 1))?;
+    }
+    writeln!(w)?;
+    end_data_array(w)?;
+    begin_data_array(w, "UInt8", "types", 1)?;
+    for _ in 0..n_cells {
+        write!(w, " {}", VTK_TRIANGLE)?;
+    }
+    writeln!(w)?;
+    end_data_array(w)?;
+    writeln!(w, "      </Cells>")?;
+
+    writeln!(w, "    </Piece>")?;
+    writeln!(w, "  </UnstructuredGrid>")?;
+    writeln!(w, "</VTKFile>")
+}
+
+// Write a ParaView collection of the .vtu files in `snapshots`, each given by its time and
+// its path relative to the .pvd file.
+pub fn write_pvd<W: Write>(w: &mut W, snapshots: &[(f64, String)]) -> io::Result<()> {
+    writeln!(w, "<?xml version=\"1.0\"?>")?;
+    writeln!(w, "<VTKFile type=\"Collection\" version=\"0.1\">")?;
+    writeln!(w, "  <Collection>")?;
+    for &(t, ref file) in snapshots.iter() {
+        writeln!(
+            w,
+            "    <DataSet timestep=\"{}\" group=\"\" part=\"0\" file=\"{}\"/>",
+            t, file
+        )?;
+    }
+    writeln!(w, "  </Collection>")?;
+    writeln!(w, "</VTKFile>")
+}
+
+fn begin_data_array<W: Write>(
+    w: &mut W,
+    data_type: &str,
+    name: &str,
+    n_components: usize,
+) -> io::Result<()> {
+    writeln!(
+        w,
+        "        <DataArray type=\"{}\" Name=\"{}\" NumberOfComponents=\"{}\" format=\"ascii\">",
+        data_type, name, n_components
+    )
+}
+
+fn end_data_array<W: Write>(w: &mut W) -> io::Result<()> {
+    writeln!(w, "        </DataArray>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_pvd;
+
+    #[test]
+    fn test_write_pvd() {
+        let snapshots = vec![
+            (0., "run_0000.vtu".to_string()),
+            (0.5, "run_0001.vtu".to_string()),
+        ];
+        let mut bytes = vec![];
+        write_pvd(&mut bytes, &snapshots).expect("could not write the collection");
+        let pvd = String::from_utf8(bytes).expect("not UTF-8");
+        assert!(pvd.contains("timestep=\"0\" group=\"\" part=\"0\" file=\"run_0000.vtu\""));
+        assert!(pvd.contains("timestep=\"0.5\" group=\"\" part=\"0\" file=\"run_0001.vtu\""));
+        assert!(pvd.trim_end().ends_with("</VTKFile>"));
+    }
+}
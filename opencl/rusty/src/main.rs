@@ -4,6 +4,8 @@
 extern crate rulinalg;
 extern crate gnuplot;
 
+mod checkpoint;
+mod convergence;
 mod distmesh;
 mod functions;
 mod galerkin_1d;
@@ -11,7 +13,13 @@ mod galerkin_2d;
 mod plot;
 
 //use galerkin_1d::advec::advec_1d_example;
+//use galerkin_1d::burgers::burgers_1d_example;
+//use galerkin_1d::euler::euler_1d_example;
+//use galerkin_1d::heat::heat_1d_example;
 //use galerkin_1d::maxwell::maxwell_1d_example;
+//use galerkin_1d::shallow_water::shallow_water_1d_example;
+//use galerkin_2d::maxwell::maxwell::maxwell_2d_convergence_example;
+//use galerkin_2d::maxwell::maxwell::maxwell_2d_vtk_example;
 use galerkin_2d::maxwell::maxwell::maxwell_2d_example;
 
 fn main() {